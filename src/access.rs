@@ -0,0 +1,135 @@
+use crate::{acl::{AceType, EntityAcl, FileSystemRights}, sid::Sid};
+
+const EVERYONE: &str = "S-1-1-0";
+const CREATOR_OWNER: &str = "S-1-3-0";
+const CREATOR_GROUP: &str = "S-1-3-1";
+const OWNER_RIGHTS: &str = "S-1-3-4";
+
+// the rights that "identity" gets on the entity of "acl" when it's a member of "groups", like the access check of
+// windows gives them for "MAXIMUM_ALLOWED". the accounts can be names like "BUILTIN\Users" or sids, the
+// well-known ones match in both forms and "Everyone" is always there.
+//
+// the aces are walked in their order: an allow ace gives the rights which weren't denied before it, and a deny
+// ace takes the ones which weren't allowed before it. windows keeps the denies first, so they win in the acls it
+// writes, but not in the ones out of order. the generic rights are mapped to the file ones.
+//
+// a null dacl gives every access. an empty one gives nothing, but the owner can always read and change the dacl
+// unless an "OWNER RIGHTS" ace says what it can do. the inherit-only aces and the ones of "CREATOR OWNER" are
+// only for the children, so they don't count here. the conditions of the callback aces can't be evaluated, so
+// they're taken as unknown like windows does: their allows don't count and their denies do. the integrity labels
+// and the privileges aren't checked.
+pub fn effective_access(acl: &EntityAcl, identity: &str, groups: &[&str]) -> FileSystemRights {
+    if acl.null_access {
+        return FileSystemRights::FULL_CONTROL;
+    }
+
+    let in_token = |trustee: &str| [identity, EVERYONE].iter().chain(groups.iter()).any(|account| same_account(account, trustee));
+    let owner = !acl.owner.is_empty() && in_token(&acl.owner);
+    let owner_rights = acl.access.iter().any(|ace| !ace.is_inherit_only() && same_account(&ace.trustee, OWNER_RIGHTS));
+    let mut granted = FileSystemRights::empty();
+    let mut denied = FileSystemRights::empty();
+
+    if owner && !owner_rights {
+        granted = FileSystemRights::READ_PERMISSIONS | FileSystemRights::CHANGE_PERMISSIONS;
+    }
+
+    for ace in &acl.access {
+        if ace.is_inherit_only() {
+            continue;
+        }
+
+        let applies = match () {
+            _ if same_account(&ace.trustee, OWNER_RIGHTS) => owner,
+            _ if same_account(&ace.trustee, CREATOR_OWNER) || same_account(&ace.trustee, CREATOR_GROUP) => false,
+            _ => in_token(&ace.trustee)
+        };
+
+        if !applies {
+            continue;
+        }
+
+        let rights = ace.rights.map_generic();
+
+        // the object aces only count without an object type, which the files never have.
+        match (ace.ace_type, ace.object_type.is_empty()) {
+            (AceType::Allow, _) | (AceType::AllowObject, true) => granted |= rights & !denied,
+            (AceType::Deny, _) | (AceType::DenyObject, true) | (AceType::DenyCallback, _) => denied |= rights & !granted,
+            _ => {}
+        }
+    }
+
+    granted
+}
+
+// the same account, by its name or its sid.
+fn same_account(first: &str, second: &str) -> bool {
+    if first.eq_ignore_ascii_case(second) {
+        return true;
+    }
+
+    let sid = |account: &str| Sid::parse(account).ok().or_else(|| Sid::from_well_known_name(account));
+
+    match (sid(first), sid(second)) {
+        (Some(first), Some(second)) => first == second,
+        _ => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{acl::entity_acl_with, runner::FixtureRunner, sddl::SecurityDescriptor};
+    use super::*;
+
+    const ENTITY_ACL: &str = include_str!("../tests/fixtures/entity_acl.json");
+
+    fn access(sddl: &str, identity: &str, groups: &[&str]) -> u32 {
+        effective_access(&SecurityDescriptor::parse(sddl).unwrap().to_entity_acl(), identity, groups).bits()
+    }
+
+    #[test]
+    fn test_entity_acl(){
+        let acl = entity_acl_with(&FixtureRunner::new().with_stdout(ENTITY_ACL), "C:\\Users\\necoo\\projects").unwrap();
+
+        // the owner, with the implicit rights on the dacl:
+        assert_eq!(effective_access(&acl, "DESKTOP-4SJ1N0T\\necoo", &["BUILTIN\\Users"]).bits(), 0x1200A9 | 0x60000);
+        // the names of the acl match the sids:
+        assert_eq!(effective_access(&acl, "S-1-5-18", &[]), FileSystemRights::FULL_CONTROL);
+        // "GENERIC_READ | GENERIC_EXECUTE", mapped:
+        assert_eq!(effective_access(&acl, "S-1-5-21-3623811015-3361044348-30300820-1013", &[]).bits(), 0x1200A9);
+        // the "CREATOR OWNER" ace is inherit-only:
+        assert_eq!(effective_access(&acl, "DESKTOP-4SJ1N0T\\stranger", &["CREATOR OWNER"]), FileSystemRights::empty());
+    }
+
+    #[test]
+    fn test_ace_order(){
+        let user = "S-1-5-21-1004336348-1177238915-682003330-1104";
+
+        assert_eq!(access("O:BAD:(D;;FW;;;S-1-5-21-1004336348-1177238915-682003330-1104)(A;;FA;;;BU)", user, &["BUILTIN\\Users"]), 0x1F01FF & !0x120116);
+        assert_eq!(access("O:BAD:(A;;FA;;;BU)(D;;FW;;;S-1-5-21-1004336348-1177238915-682003330-1104)", user, &["BUILTIN\\Users"]), 0x1F01FF);
+        assert_eq!(access("O:BAD:(D;;WD;;;WD)(A;;FA;;;BU)", user, &["BUILTIN\\Users"]), 0x1F01FF & !0x40000);
+        assert_eq!(access("O:BAD:(A;;FR;;;BU)(A;;FW;;;S-1-5-21-1004336348-1177238915-682003330-1104)", user, &["BUILTIN\\Users"]), 0x120089 | 0x120116);
+        assert_eq!(access("O:BAD:(A;;FA;;;SY)", user, &["BUILTIN\\Users"]), 0);
+    }
+
+    #[test]
+    fn test_owner_and_null_dacl(){
+        let owner = "S-1-5-21-1004336348-1177238915-682003330-1104";
+
+        assert_eq!(access("O:S-1-5-21-1004336348-1177238915-682003330-1104D:NO_ACCESS_CONTROL", "S-1-5-7", &[]), 0x1F01FF);
+        assert_eq!(access("O:S-1-5-21-1004336348-1177238915-682003330-1104D:", "S-1-5-7", &[]), 0);
+        assert_eq!(access("O:S-1-5-21-1004336348-1177238915-682003330-1104D:", owner, &[]), 0x60000);
+        // a deny can't take the implicit rights of the owner:
+        assert_eq!(access("O:S-1-5-21-1004336348-1177238915-682003330-1104D:(D;;FA;;;WD)", owner, &[]), 0x60000);
+        // the owner may be a group:
+        assert_eq!(access("O:BAD:(A;;FR;;;WD)", owner, &["BUILTIN\\Administrators"]), 0x120089 | 0x60000);
+        // "OWNER RIGHTS" replaces the implicit rights:
+        assert_eq!(access("O:S-1-5-21-1004336348-1177238915-682003330-1104D:(A;;FR;;;OW)", owner, &[]), 0x120089);
+        assert_eq!(access("O:S-1-5-21-1004336348-1177238915-682003330-1104D:(A;;FR;;;OW)", "S-1-5-7", &[]), 0);
+    }
+
+    #[test]
+    fn test_callback_aces(){
+        assert_eq!(access("D:(XA;;FA;;;WD;(@User.Division==\"Finance\"))(A;;FR;;;WD)", "S-1-5-7", &[]), 0x120089);
+        assert_eq!(access("D:(XD;;FW;;;WD;(@User.Division==\"Sales\"))(A;;FA;;;WD)", "S-1-5-7", &[]), 0x1F01FF & !0x120116);
+    }
+}
@@ -0,0 +1,750 @@
+use std::{fmt, ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub}, str::FromStr};
+
+use crate::{error::WinFileInfoError, json::{self, JsonValue}, long_path, quote, runner::CommandRunner, sddl::{DescriptorControl, SecurityDescriptor}};
+
+// access mask of an ace for files and directories, the "FileSystemRights" enum of .net. the generic rights that
+// .net has no name for are named like icacls does, since the inherit-only aces of "CREATOR OWNER" mostly have
+// them. its "Display" output is the same with powershell's, like "Modify, Synchronize", and reads back via
+// "FromStr".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct FileSystemRights(u32);
+
+impl FileSystemRights {
+    pub const READ_DATA: FileSystemRights = FileSystemRights(0x1);
+    pub const LIST_DIRECTORY: FileSystemRights = FileSystemRights(0x1);
+    pub const WRITE_DATA: FileSystemRights = FileSystemRights(0x2);
+    pub const CREATE_FILES: FileSystemRights = FileSystemRights(0x2);
+    pub const APPEND_DATA: FileSystemRights = FileSystemRights(0x4);
+    pub const CREATE_DIRECTORIES: FileSystemRights = FileSystemRights(0x4);
+    pub const READ_EXTENDED_ATTRIBUTES: FileSystemRights = FileSystemRights(0x8);
+    pub const WRITE_EXTENDED_ATTRIBUTES: FileSystemRights = FileSystemRights(0x10);
+    pub const EXECUTE_FILE: FileSystemRights = FileSystemRights(0x20);
+    pub const TRAVERSE: FileSystemRights = FileSystemRights(0x20);
+    pub const DELETE_SUBDIRECTORIES_AND_FILES: FileSystemRights = FileSystemRights(0x40);
+    pub const READ_ATTRIBUTES: FileSystemRights = FileSystemRights(0x80);
+    pub const WRITE_ATTRIBUTES: FileSystemRights = FileSystemRights(0x100);
+    pub const DELETE: FileSystemRights = FileSystemRights(0x10000);
+    pub const READ_PERMISSIONS: FileSystemRights = FileSystemRights(0x20000);
+    pub const CHANGE_PERMISSIONS: FileSystemRights = FileSystemRights(0x40000);
+    pub const TAKE_OWNERSHIP: FileSystemRights = FileSystemRights(0x80000);
+    pub const SYNCHRONIZE: FileSystemRights = FileSystemRights(0x100000);
+    pub const GENERIC_ALL: FileSystemRights = FileSystemRights(0x10000000);
+    pub const GENERIC_EXECUTE: FileSystemRights = FileSystemRights(0x20000000);
+    pub const GENERIC_WRITE: FileSystemRights = FileSystemRights(0x40000000);
+    pub const GENERIC_READ: FileSystemRights = FileSystemRights(0x80000000);
+
+    // the combinations .net has names for. they have no "Synchronize" in them, that's why powershell shows
+    // "ReadAndExecute, Synchronize" for the usual aces.
+    pub const READ: FileSystemRights = FileSystemRights(0x20089);
+    pub const WRITE: FileSystemRights = FileSystemRights(0x116);
+    pub const READ_AND_EXECUTE: FileSystemRights = FileSystemRights(0x200A9);
+    pub const MODIFY: FileSystemRights = FileSystemRights(0x301BF);
+    pub const FULL_CONTROL: FileSystemRights = FileSystemRights(0x1F01FF);
+
+    // from the biggest value to the smallest, ".ToString()" of .net takes the names in that order.
+    const NAMES: [(&'static str, FileSystemRights); 23] = [
+        ("GenericRead", FileSystemRights::GENERIC_READ),
+        ("GenericWrite", FileSystemRights::GENERIC_WRITE),
+        ("GenericExecute", FileSystemRights::GENERIC_EXECUTE),
+        ("GenericAll", FileSystemRights::GENERIC_ALL),
+        ("FullControl", FileSystemRights::FULL_CONTROL),
+        ("Synchronize", FileSystemRights::SYNCHRONIZE),
+        ("TakeOwnership", FileSystemRights::TAKE_OWNERSHIP),
+        ("ChangePermissions", FileSystemRights::CHANGE_PERMISSIONS),
+        ("Modify", FileSystemRights::MODIFY),
+        ("ReadAndExecute", FileSystemRights::READ_AND_EXECUTE),
+        ("Read", FileSystemRights::READ),
+        ("ReadPermissions", FileSystemRights::READ_PERMISSIONS),
+        ("Delete", FileSystemRights::DELETE),
+        ("Write", FileSystemRights::WRITE),
+        ("WriteAttributes", FileSystemRights::WRITE_ATTRIBUTES),
+        ("ReadAttributes", FileSystemRights::READ_ATTRIBUTES),
+        ("DeleteSubdirectoriesAndFiles", FileSystemRights::DELETE_SUBDIRECTORIES_AND_FILES),
+        ("ExecuteFile", FileSystemRights::EXECUTE_FILE),
+        ("WriteExtendedAttributes", FileSystemRights::WRITE_EXTENDED_ATTRIBUTES),
+        ("ReadExtendedAttributes", FileSystemRights::READ_EXTENDED_ATTRIBUTES),
+        ("AppendData", FileSystemRights::APPEND_DATA),
+        ("WriteData", FileSystemRights::WRITE_DATA),
+        ("ReadData", FileSystemRights::READ_DATA)
+    ];
+
+    // the other names of the same bits, which are used for directories.
+    const ALIASES: [(&'static str, FileSystemRights); 4] = [
+        ("ListDirectory", FileSystemRights::LIST_DIRECTORY),
+        ("CreateFiles", FileSystemRights::CREATE_FILES),
+        ("CreateDirectories", FileSystemRights::CREATE_DIRECTORIES),
+        ("Traverse", FileSystemRights::TRAVERSE)
+    ];
+
+    pub const fn empty() -> FileSystemRights {
+        FileSystemRights(0)
+    }
+
+    // every bit that has a name.
+    pub const fn all() -> FileSystemRights {
+        FileSystemRights(0xF01F01FF)
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    // keeps the bits which have no name too, like "AccessSystemSecurity" or "MaximumAllowed".
+    pub const fn from_bits_retain(bits: u32) -> FileSystemRights {
+        FileSystemRights(bits)
+    }
+
+    // returns "None" if there is any bit which has no name.
+    pub const fn from_bits(bits: u32) -> Option<FileSystemRights> {
+        match bits & !FileSystemRights::all().0 {
+            0 => Some(FileSystemRights(bits)),
+            _ => None
+        }
+    }
+
+    pub const fn from_bits_truncate(bits: u32) -> FileSystemRights {
+        FileSystemRights(bits & FileSystemRights::all().0)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other: FileSystemRights) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn intersects(&self, other: FileSystemRights) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn insert(&mut self, other: FileSystemRights) {
+        self.0 |= other.0
+    }
+
+    pub fn remove(&mut self, other: FileSystemRights) {
+        self.0 &= !other.0
+    }
+
+    pub fn set(&mut self, other: FileSystemRights, value: bool) {
+        match value {
+            true => self.insert(other),
+            false => self.remove(other)
+        }
+    }
+
+    // the generic rights turned into the file rights they mean, with the "GENERIC_MAPPING" of the file objects.
+    // the other bits are kept as they are.
+    pub const fn map_generic(&self) -> FileSystemRights {
+        let mut bits = self.0 & !0xF0000000;
+
+        if self.0 & FileSystemRights::GENERIC_READ.0 != 0 {
+            bits |= FileSystemRights::READ.0 | FileSystemRights::SYNCHRONIZE.0;
+        }
+
+        if self.0 & FileSystemRights::GENERIC_WRITE.0 != 0 {
+            bits |= FileSystemRights::WRITE.0 | FileSystemRights::READ_PERMISSIONS.0 | FileSystemRights::SYNCHRONIZE.0;
+        }
+
+        if self.0 & FileSystemRights::GENERIC_EXECUTE.0 != 0 {
+            bits |= FileSystemRights::EXECUTE_FILE.0 | FileSystemRights::READ_ATTRIBUTES.0 | FileSystemRights::READ_PERMISSIONS.0 | FileSystemRights::SYNCHRONIZE.0;
+        }
+
+        if self.0 & FileSystemRights::GENERIC_ALL.0 != 0 {
+            bits |= FileSystemRights::FULL_CONTROL.0;
+        }
+
+        FileSystemRights(bits)
+    }
+
+    // names like .net gives them, the biggest combinations are taken first and the names come in the order of
+    // their values.
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut remaining = self.0;
+        let mut names = vec![];
+
+        for (name, flag) in FileSystemRights::NAMES {
+            if remaining & flag.0 == flag.0 {
+                names.push(name);
+                remaining &= !flag.0;
+            }
+        }
+
+        names.reverse();
+
+        names
+    }
+
+    fn from_name(name: &str) -> Option<FileSystemRights> {
+        FileSystemRights::NAMES.iter().chain(FileSystemRights::ALIASES.iter()).find(|(flag_name, _)| flag_name.eq_ignore_ascii_case(name)).map(|(_, flag)| *flag)
+    }
+}
+
+impl BitOr for FileSystemRights {
+    type Output = FileSystemRights;
+
+    fn bitor(self, other: FileSystemRights) -> FileSystemRights {
+        FileSystemRights(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for FileSystemRights {
+    fn bitor_assign(&mut self, other: FileSystemRights) {
+        self.0 |= other.0
+    }
+}
+
+impl BitAnd for FileSystemRights {
+    type Output = FileSystemRights;
+
+    fn bitand(self, other: FileSystemRights) -> FileSystemRights {
+        FileSystemRights(self.0 & other.0)
+    }
+}
+
+impl BitAndAssign for FileSystemRights {
+    fn bitand_assign(&mut self, other: FileSystemRights) {
+        self.0 &= other.0
+    }
+}
+
+impl Sub for FileSystemRights {
+    type Output = FileSystemRights;
+
+    fn sub(self, other: FileSystemRights) -> FileSystemRights {
+        FileSystemRights(self.0 & !other.0)
+    }
+}
+
+impl Not for FileSystemRights {
+    type Output = FileSystemRights;
+
+    fn not(self) -> FileSystemRights {
+        FileSystemRights::from_bits_truncate(!self.0)
+    }
+}
+
+// "Modify, Synchronize" like powershell. bits without a name are written as a single hex number at the end and
+// an empty mask is "0".
+impl fmt::Display for FileSystemRights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self.names().into_iter().map(|name| name.to_string()).collect();
+        let unnamed_bits = self.0 & !FileSystemRights::all().0;
+
+        if unnamed_bits != 0 {
+            parts.push(format!("{:#x}", unnamed_bits));
+        }
+
+        if parts.is_empty() {
+            return write!(f, "0");
+        }
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+// accepts "Modify, Synchronize", "ListDirectory", "2032127", "0x1f01ff" and the negative numbers that powershell
+// gives for the masks with "GenericRead", like "-1610612736".
+impl FromStr for FileSystemRights {
+    type Err = WinFileInfoError;
+
+    fn from_str(input: &str) -> Result<FileSystemRights, WinFileInfoError> {
+        let mut rights = FileSystemRights::empty();
+
+        for part in input.split(',').map(|part| part.trim()) {
+            let parsed = match part.strip_prefix("0x").or_else(|| part.strip_prefix("0X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().map(FileSystemRights::from_bits_retain),
+                None if part.starts_with(|character: char| character.is_ascii_digit() || character == '-') => mask_number(part).map(FileSystemRights::from_bits_retain),
+                None => FileSystemRights::from_name(part)
+            };
+
+            match parsed {
+                Some(flags) => rights |= flags,
+                None => return Err(WinFileInfoError::unparseable(input, format!("'{}' is not a file system right", part)))
+            }
+        }
+
+        Ok(rights)
+    }
+}
+
+// access masks are "int" in .net, so the ones with the highest bit come negative.
+fn mask_number(number: &str) -> Option<u32> {
+    match number.parse::<i64>() {
+        Ok(number) if (i32::MIN as i64..=u32::MAX as i64).contains(&number) => Some(number as u32),
+        _ => None
+    }
+}
+
+// flags in the header of an ace, they tell where the ace is inherited to, whether it's inherited itself and for
+// the audit aces, which accesses are audited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct AceFlags(u8);
+
+impl AceFlags {
+    pub const OBJECT_INHERIT: AceFlags = AceFlags(0x1);
+    pub const CONTAINER_INHERIT: AceFlags = AceFlags(0x2);
+    pub const NO_PROPAGATE_INHERIT: AceFlags = AceFlags(0x4);
+    pub const INHERIT_ONLY: AceFlags = AceFlags(0x8);
+    pub const INHERITED: AceFlags = AceFlags(0x10);
+    pub const SUCCESSFUL_ACCESS: AceFlags = AceFlags(0x40);
+    pub const FAILED_ACCESS: AceFlags = AceFlags(0x80);
+
+    const NAMES: [(&'static str, AceFlags); 7] = [
+        ("ObjectInherit", AceFlags::OBJECT_INHERIT),
+        ("ContainerInherit", AceFlags::CONTAINER_INHERIT),
+        ("NoPropagateInherit", AceFlags::NO_PROPAGATE_INHERIT),
+        ("InheritOnly", AceFlags::INHERIT_ONLY),
+        ("Inherited", AceFlags::INHERITED),
+        ("SuccessfulAccess", AceFlags::SUCCESSFUL_ACCESS),
+        ("FailedAccess", AceFlags::FAILED_ACCESS)
+    ];
+
+    pub const fn empty() -> AceFlags {
+        AceFlags(0)
+    }
+
+    pub const fn all() -> AceFlags {
+        AceFlags(0xDF)
+    }
+
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn from_bits_retain(bits: u8) -> AceFlags {
+        AceFlags(bits)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other: AceFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn intersects(&self, other: AceFlags) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn insert(&mut self, other: AceFlags) {
+        self.0 |= other.0
+    }
+
+    pub fn remove(&mut self, other: AceFlags) {
+        self.0 &= !other.0
+    }
+
+    pub fn set(&mut self, other: AceFlags, value: bool) {
+        match value {
+            true => self.insert(other),
+            false => self.remove(other)
+        }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        AceFlags::NAMES.iter().filter(|(_, flag)| self.contains(*flag)).map(|(name, _)| *name).collect()
+    }
+
+    fn from_name(name: &str) -> Option<AceFlags> {
+        AceFlags::NAMES.iter().find(|(flag_name, _)| flag_name.eq_ignore_ascii_case(name)).map(|(_, flag)| *flag)
+    }
+}
+
+impl BitOr for AceFlags {
+    type Output = AceFlags;
+
+    fn bitor(self, other: AceFlags) -> AceFlags {
+        AceFlags(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for AceFlags {
+    fn bitor_assign(&mut self, other: AceFlags) {
+        self.0 |= other.0
+    }
+}
+
+impl BitAnd for AceFlags {
+    type Output = AceFlags;
+
+    fn bitand(self, other: AceFlags) -> AceFlags {
+        AceFlags(self.0 & other.0)
+    }
+}
+
+impl Sub for AceFlags {
+    type Output = AceFlags;
+
+    fn sub(self, other: AceFlags) -> AceFlags {
+        AceFlags(self.0 & !other.0)
+    }
+}
+
+impl fmt::Display for AceFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self.names().into_iter().map(|name| name.to_string()).collect();
+        let unnamed_bits = self.0 & !AceFlags::all().0;
+
+        if unnamed_bits != 0 {
+            parts.push(format!("{:#x}", unnamed_bits));
+        }
+
+        if parts.is_empty() {
+            return write!(f, "0");
+        }
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+impl FromStr for AceFlags {
+    type Err = WinFileInfoError;
+
+    fn from_str(input: &str) -> Result<AceFlags, WinFileInfoError> {
+        let mut flags = AceFlags::empty();
+
+        for part in input.split(',').map(|part| part.trim()) {
+            let parsed = match part.strip_prefix("0x").or_else(|| part.strip_prefix("0X")) {
+                Some(hex) => u8::from_str_radix(hex, 16).ok().map(AceFlags::from_bits_retain),
+                None if part.starts_with(|character: char| character.is_ascii_digit()) => part.parse::<u8>().ok().map(AceFlags::from_bits_retain),
+                None => AceFlags::from_name(part)
+            };
+
+            match parsed {
+                Some(flag) => flags |= flag,
+                None => return Err(WinFileInfoError::unparseable(input, format!("'{}' is not an ace flag", part)))
+            }
+        }
+
+        Ok(flags)
+    }
+}
+
+// "Get-Acl" gives only the first three, the others come from the sddl of the descriptors. the object ones are
+// for the active directory objects and the callback ones have a condition, which is kept in "application_data".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AceType {
+    Allow,
+    Deny,
+    Audit,
+    Alarm,
+    AllowObject,
+    DenyObject,
+    AuditObject,
+    AlarmObject,
+    AllowCallback,
+    DenyCallback,
+    AuditCallback,
+    AllowCallbackObject,
+    MandatoryLabel,
+    ResourceAttribute,
+    ScopedPolicyId
+}
+
+// a single rule of an acl, "trustee" is the account like "BUILTIN\Users", or the sid string when windows
+// couldn't resolve it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessControlEntry {
+    pub ace_type: AceType,
+    pub trustee: String,
+    pub rights: FileSystemRights,
+    pub flags: AceFlags,
+    // guids of the object aces, empty for the others.
+    pub object_type: String,
+    pub inherited_object_type: String,
+    // the condition of the callback aces or the attribute of the resource attribute aces, in its sddl form like
+    // "(@User.Department == \"Finance\")". it's kept as it is, we don't evaluate it.
+    pub application_data: String
+}
+
+impl AccessControlEntry {
+    pub fn new(ace_type: AceType, trustee: impl Into<String>, rights: FileSystemRights, flags: AceFlags) -> AccessControlEntry {
+        AccessControlEntry {
+            ace_type,
+            trustee: trustee.into(),
+            rights,
+            flags,
+            object_type: "".to_string(),
+            inherited_object_type: "".to_string(),
+            application_data: "".to_string()
+        }
+    }
+
+    // inherited from a parent, instead of being set on the entity itself.
+    pub fn is_inherited(&self) -> bool {
+        self.flags.contains(AceFlags::INHERITED)
+    }
+
+    // applies only to the children, not to the entity itself.
+    pub fn is_inherit_only(&self) -> bool {
+        self.flags.contains(AceFlags::INHERIT_ONLY)
+    }
+
+    // the "InheritanceFlags" of .net, which children get that ace.
+    pub fn inheritance(&self) -> AceFlags {
+        self.flags & (AceFlags::OBJECT_INHERIT | AceFlags::CONTAINER_INHERIT)
+    }
+
+    // the "PropagationFlags" of .net, how that ace passes to the children.
+    pub fn propagation(&self) -> AceFlags {
+        self.flags & (AceFlags::NO_PROPAGATE_INHERIT | AceFlags::INHERIT_ONLY)
+    }
+}
+
+// the security descriptor of an entity. "access" is the dacl and "audit" is the sacl, in the order windows
+// keeps them. the sacl is only there when we could read it, which needs "SeSecurityPrivilege".
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EntityAcl {
+    pub owner: String,
+    pub group: String,
+    pub access: Vec<AccessControlEntry>,
+    pub audit: Vec<AccessControlEntry>,
+    // the entity doesn't inherit the aces of its parent.
+    pub access_protected: bool,
+    pub audit_protected: bool,
+    // the dacl is null, which gives every access to everyone. an empty "access" with a dacl that is there gives
+    // no access to anyone but the owner.
+    pub null_access: bool,
+    // the whole descriptor in sddl form as windows gives it, empty on the native backend.
+    pub sddl: String
+}
+
+#[cfg(target_os = "windows")]
+pub fn entity_acl(path: &str) -> Result<EntityAcl, WinFileInfoError> {
+    entity_acl_with(&crate::runner::PowerShellRunner, path)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn entity_acl(path: &str) -> Result<EntityAcl, WinFileInfoError> {
+    crate::native::entity_acl(path)
+}
+
+// the owner, the group and every ace of the entity from "Get-Acl". the sacl is asked first and we fall back to
+// the acl without it when we have no privilege to read it.
+pub fn entity_acl_with<R: CommandRunner>(runner: &R, path: &str) -> Result<EntityAcl, WinFileInfoError> {
+    let rule = "Type = [int]$_.AccessControlType; Identity = $_.IdentityReference.Value; Rights = [int]$_.FileSystemRights; Inherited = $_.IsInherited; Inheritance = [int]$_.InheritanceFlags; Propagation = [int]$_.PropagationFlags";
+    let audit_rule = "Identity = $_.IdentityReference.Value; Rights = [int]$_.FileSystemRights; Inherited = $_.IsInherited; Inheritance = [int]$_.InheritanceFlags; Propagation = [int]$_.PropagationFlags; AuditFlags = [int]$_.AuditFlags";
+
+    let command = format!(
+        "$path = {}; $acl = try {{ Get-Acl -LiteralPath $path -Audit -ErrorAction Stop }} catch {{ Get-Acl -LiteralPath $path }}; if ($acl) {{ [ordered]@{{ Owner = $acl.Owner; Group = $acl.Group; Sddl = $acl.Sddl; AccessProtected = $acl.AreAccessRulesProtected; AuditProtected = $acl.AreAuditRulesProtected; Access = @($acl.Access | ForEach-Object {{ [ordered]@{{ {} }} }}); Audit = @($acl.Audit | ForEach-Object {{ [ordered]@{{ {} }} }}) }} | ConvertTo-Json -Compress -Depth 3 }}",
+        quote::single_quoted(&long_path::promote_long_path(path)), rule, audit_rule
+    );
+
+    let stdout = crate::run_query(runner, path, &command)?;
+
+    if stdout.trim().is_empty() {
+        return Err(WinFileInfoError::PathNotFound { path: path.to_string(), stderr: None });
+    }
+
+    parse_entity_acl(&stdout)
+}
+
+fn parse_entity_acl(stdout: &str) -> Result<EntityAcl, WinFileInfoError> {
+    let record = json::parse(stdout)?;
+
+    if !matches!(record, JsonValue::Object(_)) {
+        return Err(crate::invalid_entity(&record, "expected an acl object".to_string()));
+    }
+
+    let sddl = crate::optional_string_field(&record, "Sddl")?;
+
+    // .net has no way to tell a null dacl, only the sddl does.
+    let null_access = match SecurityDescriptor::parse(&sddl) {
+        Ok(descriptor) => descriptor.dacl.is_none() && descriptor.control.contains(DescriptorControl::DACL_PRESENT),
+        Err(_) => false
+    };
+
+    Ok(EntityAcl {
+        owner: crate::optional_string_field(&record, "Owner")?,
+        group: crate::optional_string_field(&record, "Group")?,
+        access: rules_field(&record, "Access")?.iter().map(access_rule).collect::<Result<_, _>>()?,
+        audit: rules_field(&record, "Audit")?.iter().map(audit_rule).collect::<Result<_, _>>()?,
+        access_protected: bool_field(&record, "AccessProtected")?,
+        audit_protected: bool_field(&record, "AuditProtected")?,
+        null_access,
+        sddl
+    })
+}
+
+// "ConvertTo-Json" of the older powershell versions writes a single rule without its array.
+fn rules_field<'a>(record: &'a JsonValue, field: &str) -> Result<Vec<&'a JsonValue>, WinFileInfoError> {
+    match record.get(field) {
+        Some(JsonValue::Array(rules)) => Ok(rules.iter().collect()),
+        Some(JsonValue::Null) | None => Ok(vec![]),
+        Some(rule @ JsonValue::Object(_)) => Ok(vec![rule]),
+        Some(_) => Err(crate::invalid_entity(record, format!("'{}' field of the acl is not a list of rules", field)))
+    }
+}
+
+fn bool_field(record: &JsonValue, field: &str) -> Result<bool, WinFileInfoError> {
+    match record.get(field) {
+        Some(JsonValue::Bool(value)) => Ok(*value),
+        Some(JsonValue::Null) | None => Ok(false),
+        Some(_) => Err(crate::invalid_entity(record, format!("'{}' field of the acl is not a boolean", field)))
+    }
+}
+
+fn number_field(record: &JsonValue, field: &str) -> Result<u32, WinFileInfoError> {
+    match record.get(field) {
+        Some(JsonValue::Number(number)) => mask_number(number).ok_or(crate::invalid_entity(record, format!("'{}' field of the rule is not a valid number", field))),
+        Some(JsonValue::Null) | None => Ok(0),
+        Some(_) => Err(crate::invalid_entity(record, format!("'{}' field of the rule is not a number", field)))
+    }
+}
+
+// the header flags of the ace, from the "InheritanceFlags", "PropagationFlags" and "IsInherited" of .net.
+fn rule_flags(rule: &JsonValue) -> Result<AceFlags, WinFileInfoError> {
+    let inheritance = number_field(rule, "Inheritance")?;
+    let propagation = number_field(rule, "Propagation")?;
+    let mut flags = AceFlags::empty();
+
+    flags.set(AceFlags::CONTAINER_INHERIT, inheritance & 0x1 != 0);
+    flags.set(AceFlags::OBJECT_INHERIT, inheritance & 0x2 != 0);
+    flags.set(AceFlags::NO_PROPAGATE_INHERIT, propagation & 0x1 != 0);
+    flags.set(AceFlags::INHERIT_ONLY, propagation & 0x2 != 0);
+    flags.set(AceFlags::INHERITED, bool_field(rule, "Inherited")?);
+
+    Ok(flags)
+}
+
+fn access_rule(rule: &&JsonValue) -> Result<AccessControlEntry, WinFileInfoError> {
+    let ace_type = match number_field(rule, "Type")? {
+        0 => AceType::Allow,
+        1 => AceType::Deny,
+        _ => return Err(crate::invalid_entity(rule, "'Type' field of the rule is not allow or deny".to_string()))
+    };
+
+    Ok(AccessControlEntry::new(ace_type, crate::required_string_field(rule, "Identity")?, FileSystemRights::from_bits_retain(number_field(rule, "Rights")?), rule_flags(rule)?))
+}
+
+fn audit_rule(rule: &&JsonValue) -> Result<AccessControlEntry, WinFileInfoError> {
+    let audit_flags = number_field(rule, "AuditFlags")?;
+    let mut flags = rule_flags(rule)?;
+
+    flags.set(AceFlags::SUCCESSFUL_ACCESS, audit_flags & 0x1 != 0);
+    flags.set(AceFlags::FAILED_ACCESS, audit_flags & 0x2 != 0);
+
+    Ok(AccessControlEntry::new(AceType::Audit, crate::required_string_field(rule, "Identity")?, FileSystemRights::from_bits_retain(number_field(rule, "Rights")?), flags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::FixtureRunner;
+
+    const ENTITY_ACL: &str = include_str!("../tests/fixtures/entity_acl.json");
+    const ENTITY_ACL_WITH_AUDIT: &str = include_str!("../tests/fixtures/entity_acl_with_audit.json");
+
+    #[test]
+    fn test_rights_names(){
+        assert_eq!(FileSystemRights::from_bits_retain(0x1F01FF).to_string(), "FullControl");
+        assert_eq!(FileSystemRights::from_bits_retain(0x1301BF).to_string(), "Modify, Synchronize");
+        assert_eq!(FileSystemRights::from_bits_retain(0x1200A9).to_string(), "ReadAndExecute, Synchronize");
+        assert_eq!(FileSystemRights::from_bits_retain(0x100116).to_string(), "Write, Synchronize");
+        assert_eq!(FileSystemRights::from_bits_retain(0x10000000).to_string(), "GenericAll");
+        assert_eq!(FileSystemRights::from_bits_retain(0x1000000).to_string(), "0x1000000");
+        assert_eq!(FileSystemRights::empty().to_string(), "0");
+
+        assert_eq!("ListDirectory, Traverse".parse::<FileSystemRights>().unwrap(), FileSystemRights::READ_DATA | FileSystemRights::EXECUTE_FILE);
+        assert_eq!("-1610612736".parse::<FileSystemRights>().unwrap(), FileSystemRights::GENERIC_READ | FileSystemRights::GENERIC_EXECUTE);
+        assert_eq!("0x1f01ff".parse::<FileSystemRights>().unwrap(), FileSystemRights::FULL_CONTROL);
+        assert!("Modify, Everything".parse::<FileSystemRights>().is_err());
+
+        for bits in [0x0, 0x1F01FF, 0x1301BF, 0x1200A9, 0x120089, 0x116, 0x20000, 0xE0000000, 0x1000041] {
+            let rights = FileSystemRights::from_bits_retain(bits);
+
+            assert_eq!(rights.to_string().parse::<FileSystemRights>().unwrap(), rights);
+        }
+    }
+
+    #[test]
+    fn test_map_generic(){
+        assert_eq!(FileSystemRights::GENERIC_ALL.map_generic(), FileSystemRights::FULL_CONTROL);
+        assert_eq!((FileSystemRights::GENERIC_READ | FileSystemRights::GENERIC_EXECUTE).map_generic().to_string(), "ReadAndExecute, Synchronize");
+        assert_eq!(FileSystemRights::GENERIC_WRITE.map_generic().to_string(), "Write, ReadPermissions, Synchronize");
+        assert_eq!(FileSystemRights::DELETE.map_generic(), FileSystemRights::DELETE);
+    }
+
+    #[test]
+    fn test_ace_flags(){
+        let flags: AceFlags = "ContainerInherit, ObjectInherit, Inherited".parse().unwrap();
+
+        assert_eq!(flags, AceFlags::OBJECT_INHERIT | AceFlags::CONTAINER_INHERIT | AceFlags::INHERITED);
+        assert_eq!(flags.to_string(), "ObjectInherit, ContainerInherit, Inherited");
+        assert_eq!("0x18".parse::<AceFlags>().unwrap(), AceFlags::INHERIT_ONLY | AceFlags::INHERITED);
+        assert_eq!(AceFlags::empty().to_string(), "0");
+        assert!("Sideways".parse::<AceFlags>().is_err());
+    }
+
+    #[test]
+    fn test_entity_acl_with_fixture(){
+        let runner = FixtureRunner::new().with_stdout(ENTITY_ACL);
+        let acl = entity_acl_with(&runner, "C:\\Users\\necoo\\projects").unwrap();
+
+        assert!(runner.commands()[0].starts_with("$path = 'C:\\Users\\necoo\\projects'; $acl = try { Get-Acl -LiteralPath $path -Audit"));
+        assert_eq!(acl.owner, "DESKTOP-4SJ1N0T\\necoo");
+        assert_eq!(acl.group, "DESKTOP-4SJ1N0T\\None");
+        assert!(acl.sddl.starts_with("O:S-1-5-21-"));
+        assert!(!acl.access_protected);
+        assert!(acl.audit.is_empty());
+        assert_eq!(acl.access.len(), 6);
+
+        let deny = &acl.access[0];
+
+        assert_eq!(deny.ace_type, AceType::Deny);
+        assert_eq!(deny.trustee, "DESKTOP-4SJ1N0T\\guest");
+        assert_eq!(deny.rights, FileSystemRights::WRITE | FileSystemRights::DELETE);
+        assert!(!deny.is_inherited());
+        assert_eq!(deny.inheritance(), AceFlags::OBJECT_INHERIT | AceFlags::CONTAINER_INHERIT);
+
+        // an orphaned sid can't be resolved to a name:
+        assert_eq!(acl.access[1].trustee, "S-1-5-21-3623811015-3361044348-30300820-1013");
+        assert_eq!(acl.access[1].rights, FileSystemRights::GENERIC_READ | FileSystemRights::GENERIC_EXECUTE);
+
+        let system = &acl.access[2];
+
+        assert_eq!(system.ace_type, AceType::Allow);
+        assert_eq!(system.trustee, "NT AUTHORITY\\SYSTEM");
+        assert_eq!(system.rights, FileSystemRights::FULL_CONTROL);
+        assert!(system.is_inherited());
+
+        // "CREATOR OWNER" gets generic rights only for the children:
+        let creator_owner = &acl.access[4];
+
+        assert_eq!(creator_owner.trustee, "CREATOR OWNER");
+        assert_eq!(creator_owner.rights, FileSystemRights::GENERIC_ALL);
+        assert!(creator_owner.is_inherit_only());
+        assert_eq!(creator_owner.propagation(), AceFlags::INHERIT_ONLY);
+
+        let users = &acl.access[5];
+
+        assert_eq!(users.rights.to_string(), "ReadAndExecute, Synchronize");
+        assert_eq!(users.flags, AceFlags::OBJECT_INHERIT | AceFlags::CONTAINER_INHERIT | AceFlags::INHERITED);
+    }
+
+    #[test]
+    fn test_entity_acl_with_audit_fixture(){
+        let runner = FixtureRunner::new().with_stdout(ENTITY_ACL_WITH_AUDIT);
+        let acl = entity_acl_with(&runner, "D:\\shares\\finance\\budget.xlsx").unwrap();
+
+        assert!(acl.access_protected);
+        assert_eq!(acl.access.len(), 2);
+        assert_eq!(acl.audit.len(), 2);
+        assert_eq!(acl.audit[0].ace_type, AceType::Audit);
+        assert_eq!(acl.audit[0].trustee, "Everyone");
+        assert_eq!(acl.audit[0].flags, AceFlags::FAILED_ACCESS);
+        assert_eq!(acl.audit[1].flags, AceFlags::SUCCESSFUL_ACCESS | AceFlags::FAILED_ACCESS | AceFlags::INHERITED);
+        assert_eq!(acl.audit[1].rights, FileSystemRights::DELETE | FileSystemRights::CHANGE_PERMISSIONS | FileSystemRights::TAKE_OWNERSHIP);
+    }
+
+    #[test]
+    fn test_entity_acl_errors(){
+        let runner = FixtureRunner::new().with_stderr("Get-Acl : Cannot find path 'C:\\nothing' because it does not exist.\n    + FullyQualifiedErrorId : GetAcl_PathNotFound_Exception,Microsoft.PowerShell.Commands.GetAclCommand");
+
+        assert!(matches!(entity_acl_with(&runner, "C:\\nothing"), Err(WinFileInfoError::PathNotFound { .. })));
+        assert!(matches!(entity_acl_with(&FixtureRunner::new().with_stdout("{\"Owner\":\"x\",\"Access\":[{\"Type\":7,\"Identity\":\"Everyone\"}]}"), "C:\\x"), Err(WinFileInfoError::UnparseableRecord { .. })));
+        assert!(matches!(entity_acl_with(&FixtureRunner::new().with_stdout("[]"), "C:\\x"), Err(WinFileInfoError::UnparseableRecord { .. })));
+    }
+}
@@ -0,0 +1,274 @@
+use std::{fmt, ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub}, str::FromStr};
+
+use crate::error::WinFileInfoError;
+
+// win32 file attribute flags of an entity, the "dwFileAttributes" of "WIN32_FIND_DATA" and "Attributes" property of
+// powershell. it can be parsed from both the "ReadOnly, Archive" form of powershell and the numeric dword form,
+// and it's "Display" output reads back via "FromStr".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct FileAttributes(u32);
+
+impl FileAttributes {
+    pub const READONLY: FileAttributes = FileAttributes(0x1);
+    pub const HIDDEN: FileAttributes = FileAttributes(0x2);
+    pub const SYSTEM: FileAttributes = FileAttributes(0x4);
+    pub const DIRECTORY: FileAttributes = FileAttributes(0x10);
+    pub const ARCHIVE: FileAttributes = FileAttributes(0x20);
+    pub const DEVICE: FileAttributes = FileAttributes(0x40);
+    pub const NORMAL: FileAttributes = FileAttributes(0x80);
+    pub const TEMPORARY: FileAttributes = FileAttributes(0x100);
+    pub const SPARSE_FILE: FileAttributes = FileAttributes(0x200);
+    pub const REPARSE_POINT: FileAttributes = FileAttributes(0x400);
+    pub const COMPRESSED: FileAttributes = FileAttributes(0x800);
+    pub const OFFLINE: FileAttributes = FileAttributes(0x1000);
+    pub const NOT_CONTENT_INDEXED: FileAttributes = FileAttributes(0x2000);
+    pub const ENCRYPTED: FileAttributes = FileAttributes(0x4000);
+    pub const INTEGRITY_STREAM: FileAttributes = FileAttributes(0x8000);
+    pub const NO_SCRUB_DATA: FileAttributes = FileAttributes(0x20000);
+    pub const RECALL_ON_OPEN: FileAttributes = FileAttributes(0x40000);
+    pub const PINNED: FileAttributes = FileAttributes(0x80000);
+    pub const UNPINNED: FileAttributes = FileAttributes(0x100000);
+    pub const RECALL_ON_DATA_ACCESS: FileAttributes = FileAttributes(0x400000);
+
+    // names are the same with .net's "FileAttributes" enum, the ones which .net has no name for follow its style.
+    const NAMES: [(&'static str, FileAttributes); 20] = [
+        ("ReadOnly", FileAttributes::READONLY),
+        ("Hidden", FileAttributes::HIDDEN),
+        ("System", FileAttributes::SYSTEM),
+        ("Directory", FileAttributes::DIRECTORY),
+        ("Archive", FileAttributes::ARCHIVE),
+        ("Device", FileAttributes::DEVICE),
+        ("Normal", FileAttributes::NORMAL),
+        ("Temporary", FileAttributes::TEMPORARY),
+        ("SparseFile", FileAttributes::SPARSE_FILE),
+        ("ReparsePoint", FileAttributes::REPARSE_POINT),
+        ("Compressed", FileAttributes::COMPRESSED),
+        ("Offline", FileAttributes::OFFLINE),
+        ("NotContentIndexed", FileAttributes::NOT_CONTENT_INDEXED),
+        ("Encrypted", FileAttributes::ENCRYPTED),
+        ("IntegrityStream", FileAttributes::INTEGRITY_STREAM),
+        ("NoScrubData", FileAttributes::NO_SCRUB_DATA),
+        ("RecallOnOpen", FileAttributes::RECALL_ON_OPEN),
+        ("Pinned", FileAttributes::PINNED),
+        ("Unpinned", FileAttributes::UNPINNED),
+        ("RecallOnDataAccess", FileAttributes::RECALL_ON_DATA_ACCESS)
+    ];
+
+    pub const fn empty() -> FileAttributes {
+        FileAttributes(0)
+    }
+
+    // every flag that has a name.
+    pub const fn all() -> FileAttributes {
+        FileAttributes(0x5EFFF7)
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    // keeps the bits which have no name too, since windows adds new attributes from time to time.
+    pub const fn from_bits_retain(bits: u32) -> FileAttributes {
+        FileAttributes(bits)
+    }
+
+    // returns "None" if there is any bit which has no name.
+    pub const fn from_bits(bits: u32) -> Option<FileAttributes> {
+        match bits & !FileAttributes::all().0 {
+            0 => Some(FileAttributes(bits)),
+            _ => None
+        }
+    }
+
+    pub const fn from_bits_truncate(bits: u32) -> FileAttributes {
+        FileAttributes(bits & FileAttributes::all().0)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(&self, other: FileAttributes) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn intersects(&self, other: FileAttributes) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn insert(&mut self, other: FileAttributes) {
+        self.0 |= other.0
+    }
+
+    pub fn remove(&mut self, other: FileAttributes) {
+        self.0 &= !other.0
+    }
+
+    pub fn set(&mut self, other: FileAttributes, value: bool) {
+        match value {
+            true => self.insert(other),
+            false => self.remove(other)
+        }
+    }
+
+    // names of the flags it has, in the order of their bits.
+    pub fn names(&self) -> Vec<&'static str> {
+        FileAttributes::NAMES.iter().filter(|(_, flag)| self.contains(*flag)).map(|(name, _)| *name).collect()
+    }
+
+    fn from_name(name: &str) -> Option<FileAttributes> {
+        FileAttributes::NAMES.iter().find(|(flag_name, _)| flag_name.eq_ignore_ascii_case(name)).map(|(_, flag)| *flag)
+    }
+}
+
+impl BitOr for FileAttributes {
+    type Output = FileAttributes;
+
+    fn bitor(self, other: FileAttributes) -> FileAttributes {
+        FileAttributes(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for FileAttributes {
+    fn bitor_assign(&mut self, other: FileAttributes) {
+        self.0 |= other.0
+    }
+}
+
+impl BitAnd for FileAttributes {
+    type Output = FileAttributes;
+
+    fn bitand(self, other: FileAttributes) -> FileAttributes {
+        FileAttributes(self.0 & other.0)
+    }
+}
+
+impl BitAndAssign for FileAttributes {
+    fn bitand_assign(&mut self, other: FileAttributes) {
+        self.0 &= other.0
+    }
+}
+
+impl Sub for FileAttributes {
+    type Output = FileAttributes;
+
+    fn sub(self, other: FileAttributes) -> FileAttributes {
+        FileAttributes(self.0 & !other.0)
+    }
+}
+
+impl Not for FileAttributes {
+    type Output = FileAttributes;
+
+    fn not(self) -> FileAttributes {
+        FileAttributes::from_bits_truncate(!self.0)
+    }
+}
+
+// "ReadOnly, Archive" like powershell. bits without a name are written as a single hex number at the end and
+// an empty set is "0", like .net does.
+impl fmt::Display for FileAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self.names().into_iter().map(|name| name.to_string()).collect();
+        let unnamed_bits = self.0 & !FileAttributes::all().0;
+
+        if unnamed_bits != 0 {
+            parts.push(format!("{:#x}", unnamed_bits));
+        }
+
+        if parts.is_empty() {
+            return write!(f, "0");
+        }
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+// accepts "ReadOnly, Archive", "33", "0x21" and mixes of them like "Archive, 524288", which is how .net writes
+// the attributes that it has no name for.
+impl FromStr for FileAttributes {
+    type Err = WinFileInfoError;
+
+    fn from_str(input: &str) -> Result<FileAttributes, WinFileInfoError> {
+        let mut attributes = FileAttributes::empty();
+
+        for part in input.split(',').map(|part| part.trim()) {
+            let parsed = match part.strip_prefix("0x").or_else(|| part.strip_prefix("0X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().map(FileAttributes::from_bits_retain),
+                None if part.starts_with(|character: char| character.is_ascii_digit()) => part.parse::<u32>().ok().map(FileAttributes::from_bits_retain),
+                None => FileAttributes::from_name(part)
+            };
+
+            match parsed {
+                Some(flags) => attributes |= flags,
+                None => return Err(WinFileInfoError::unparseable(input, format!("'{}' is not a file attribute", part)))
+            }
+        }
+
+        Ok(attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_names(){
+        let attributes: FileAttributes = "Hidden, System, Directory, ReparsePoint".parse().unwrap();
+
+        assert_eq!(attributes, FileAttributes::HIDDEN | FileAttributes::SYSTEM | FileAttributes::DIRECTORY | FileAttributes::REPARSE_POINT);
+        assert!(attributes.contains(FileAttributes::DIRECTORY | FileAttributes::REPARSE_POINT));
+        assert!(!attributes.intersects(FileAttributes::ARCHIVE));
+        assert_eq!("archive,readonly".parse::<FileAttributes>().unwrap(), FileAttributes::ARCHIVE | FileAttributes::READONLY);
+    }
+
+    #[test]
+    fn test_parse_dword(){
+        assert_eq!("32".parse::<FileAttributes>().unwrap(), FileAttributes::ARCHIVE);
+        assert_eq!("0x410".parse::<FileAttributes>().unwrap(), FileAttributes::DIRECTORY | FileAttributes::REPARSE_POINT);
+        assert_eq!("Archive, 524288".parse::<FileAttributes>().unwrap(), FileAttributes::ARCHIVE | FileAttributes::PINNED);
+        assert_eq!("0".parse::<FileAttributes>().unwrap(), FileAttributes::empty());
+    }
+
+    #[test]
+    fn test_display_round_trip(){
+        let samples = [
+            FileAttributes::empty(),
+            FileAttributes::ARCHIVE,
+            FileAttributes::all(),
+            FileAttributes::RECALL_ON_DATA_ACCESS | FileAttributes::UNPINNED | FileAttributes::OFFLINE,
+            FileAttributes::from_bits_retain(0x10000 | 0x20)
+        ];
+
+        for attributes in samples {
+            assert_eq!(attributes.to_string().parse::<FileAttributes>().unwrap(), attributes);
+        }
+
+        assert_eq!((FileAttributes::READONLY | FileAttributes::ARCHIVE).to_string(), "ReadOnly, Archive");
+        assert_eq!(FileAttributes::from_bits_retain(0x10020).to_string(), "Archive, 0x10000");
+        assert_eq!(FileAttributes::empty().to_string(), "0");
+    }
+
+    #[test]
+    fn test_bits(){
+        assert_eq!(FileAttributes::all().names().len(), 20);
+        assert_eq!(FileAttributes::from_bits(0x10000), None);
+        assert_eq!(FileAttributes::from_bits_truncate(0x10020), FileAttributes::ARCHIVE);
+
+        let mut attributes = FileAttributes::READONLY | FileAttributes::HIDDEN;
+        attributes.remove(FileAttributes::READONLY);
+        attributes.set(FileAttributes::ENCRYPTED, true);
+
+        assert_eq!(attributes, FileAttributes::HIDDEN | FileAttributes::ENCRYPTED);
+        assert_eq!(attributes - FileAttributes::HIDDEN, FileAttributes::ENCRYPTED);
+        assert!(!(!attributes).intersects(attributes));
+    }
+
+    #[test]
+    fn test_parse_errors(){
+        assert!("Archive, Shiny".parse::<FileAttributes>().is_err());
+        assert!("".parse::<FileAttributes>().is_err());
+        assert!("0xZZ".parse::<FileAttributes>().is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use std::{error, fmt, io};
+
+// every error that functions of that crate return. the ones which come from powershell carry its stderr, so the
+// original message is never lost.
+#[derive(Debug)]
+pub enum WinFileInfoError {
+    // there is no entity on given path.
+    PathNotFound { path: String, stderr: Option<String> },
+    // the entity is there but we have no permission to read it.
+    AccessDenied { path: String, stderr: Option<String> },
+    // powershell couldn't be started, or the runner has nothing to run the command with.
+    ShellNotAvailable(io::Error),
+    // output of the command is not utf8, mostly because the code page of the terminal couldn't be changed.
+    NonUtf8Output { stderr: Option<String> },
+    // output of the command is not something we can read, "record" is the text that we couldn't read.
+    UnparseableRecord { record: String, reason: String },
+    // given path matches more than one entity, which happens when it has wildcards.
+    AmbiguousMatch { path: String, matches: usize },
+    // command failed for a reason that we don't know.
+    CommandFailed { stderr: String },
+    // given path is not a valid windows path, like an unc path without a share.
+    InvalidPath { path: String, reason: String },
+    // errors that don't come from the shell, like not being able to read the current directory.
+    Io(io::Error)
+}
+
+impl WinFileInfoError {
+    pub(crate) fn invalid_path(path: impl Into<String>, reason: impl Into<String>) -> WinFileInfoError {
+        WinFileInfoError::InvalidPath { path: path.into(), reason: reason.into() }
+    }
+
+    pub(crate) fn unparseable(record: impl Into<String>, reason: impl Into<String>) -> WinFileInfoError {
+        WinFileInfoError::UnparseableRecord { record: record.into(), reason: reason.into() }
+    }
+
+    // decides what went wrong from the stderr of powershell. "FullyQualifiedErrorId" of the powershell errors
+    // is the same in every language, so we look for it instead of the messages.
+    pub(crate) fn from_stderr(path: &str, stderr: &str) -> WinFileInfoError {
+        let stderr = stderr.trim();
+
+        if ["PathNotFound", "ItemNotFound", "ObjectNotFound"].iter().any(|id| stderr.contains(id)) {
+            return WinFileInfoError::PathNotFound { path: path.to_string(), stderr: Some(stderr.to_string()) };
+        }
+
+        if ["UnauthorizedAccess", "PermissionDenied"].iter().any(|id| stderr.contains(id)) {
+            return WinFileInfoError::AccessDenied { path: path.to_string(), stderr: Some(stderr.to_string()) };
+        }
+
+        WinFileInfoError::CommandFailed { stderr: stderr.to_string() }
+    }
+
+    // same error for another path of a batch, "io::Error" can't be cloned so it's rebuilt from its kind and message.
+    pub(crate) fn for_path(&self, path: &str) -> WinFileInfoError {
+        let copy_io = |error: &io::Error| io::Error::new(error.kind(), error.to_string());
+
+        match self {
+            WinFileInfoError::PathNotFound { stderr, .. } => WinFileInfoError::PathNotFound { path: path.to_string(), stderr: stderr.clone() },
+            WinFileInfoError::AccessDenied { stderr, .. } => WinFileInfoError::AccessDenied { path: path.to_string(), stderr: stderr.clone() },
+            WinFileInfoError::ShellNotAvailable(error) => WinFileInfoError::ShellNotAvailable(copy_io(error)),
+            WinFileInfoError::NonUtf8Output { stderr } => WinFileInfoError::NonUtf8Output { stderr: stderr.clone() },
+            WinFileInfoError::UnparseableRecord { record, reason } => WinFileInfoError::unparseable(record.clone(), reason.clone()),
+            WinFileInfoError::AmbiguousMatch { matches, .. } => WinFileInfoError::AmbiguousMatch { path: path.to_string(), matches: *matches },
+            WinFileInfoError::CommandFailed { stderr } => WinFileInfoError::CommandFailed { stderr: stderr.clone() },
+            WinFileInfoError::InvalidPath { reason, .. } => WinFileInfoError::InvalidPath { path: path.to_string(), reason: reason.clone() },
+            WinFileInfoError::Io(error) => WinFileInfoError::Io(copy_io(error))
+        }
+    }
+
+    // stderr of powershell, if that error came from it.
+    pub fn stderr(&self) -> Option<&str> {
+        match self {
+            WinFileInfoError::PathNotFound { stderr, .. } => stderr.as_deref(),
+            WinFileInfoError::AccessDenied { stderr, .. } => stderr.as_deref(),
+            WinFileInfoError::NonUtf8Output { stderr } => stderr.as_deref(),
+            WinFileInfoError::CommandFailed { stderr } => Some(stderr),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for WinFileInfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WinFileInfoError::PathNotFound { path, .. } => write!(f, "there is no entity on '{}'", path),
+            WinFileInfoError::AccessDenied { path, .. } => write!(f, "access to '{}' is denied", path),
+            WinFileInfoError::ShellNotAvailable(error) => write!(f, "powershell is not available: {}", error),
+            WinFileInfoError::NonUtf8Output { .. } => write!(f, "output of powershell is not valid utf8"),
+            WinFileInfoError::UnparseableRecord { record, reason } => write!(f, "cannot parse the output of powershell, {}: {}", reason, record),
+            WinFileInfoError::AmbiguousMatch { path, matches } => write!(f, "'{}' matches {} entities instead of one", path, matches),
+            WinFileInfoError::CommandFailed { stderr } => write!(f, "powershell command failed: {}", stderr),
+            WinFileInfoError::InvalidPath { path, reason } => write!(f, "'{}' is not a valid path, {}", path, reason),
+            WinFileInfoError::Io(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl error::Error for WinFileInfoError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            WinFileInfoError::ShellNotAvailable(error) | WinFileInfoError::Io(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+// for the code that still works with "std::io::Error", which is what that crate returned before.
+impl From<WinFileInfoError> for io::Error {
+    fn from(error: WinFileInfoError) -> io::Error {
+        let kind = match &error {
+            WinFileInfoError::PathNotFound { .. } => io::ErrorKind::NotFound,
+            WinFileInfoError::AccessDenied { .. } => io::ErrorKind::PermissionDenied,
+            WinFileInfoError::ShellNotAvailable(error) | WinFileInfoError::Io(error) => error.kind(),
+            WinFileInfoError::NonUtf8Output { .. } | WinFileInfoError::UnparseableRecord { .. } => io::ErrorKind::InvalidData,
+            WinFileInfoError::AmbiguousMatch { .. } | WinFileInfoError::CommandFailed { .. } => io::ErrorKind::Other,
+            WinFileInfoError::InvalidPath { .. } => io::ErrorKind::InvalidInput
+        };
+
+        io::Error::new(kind, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_stderr(){
+        let not_found = "Get-Item : Cannot find path 'C:\\missing' because it does not exist.\r\n    + CategoryInfo          : ObjectNotFound: (C:\\missing:String) [Get-Item], ItemNotFoundException\r\n    + FullyQualifiedErrorId : PathNotFound,Microsoft.PowerShell.Commands.GetItemCommand";
+        let denied = "Get-ChildItem : Der Zugriff auf den Pfad \"C:\\secret\" wurde verweigert.\r\n    + FullyQualifiedErrorId : DirUnauthorizedAccessError,Microsoft.PowerShell.Commands.GetChildItemCommand";
+
+        assert!(matches!(WinFileInfoError::from_stderr("C:\\missing", not_found), WinFileInfoError::PathNotFound { .. }));
+        assert!(matches!(WinFileInfoError::from_stderr("C:\\secret", denied), WinFileInfoError::AccessDenied { .. }));
+        assert!(matches!(WinFileInfoError::from_stderr("C:\\", "something else"), WinFileInfoError::CommandFailed { .. }));
+        assert_eq!(WinFileInfoError::from_stderr("C:\\secret", denied).stderr(), Some(denied));
+    }
+
+    #[test]
+    fn test_into_io_error(){
+        let error: io::Error = WinFileInfoError::PathNotFound { path: "C:\\missing".to_string(), stderr: None }.into();
+
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(error.to_string(), "there is no entity on 'C:\\missing'");
+    }
+}
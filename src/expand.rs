@@ -0,0 +1,303 @@
+use std::{fmt, str::FromStr};
+
+use crate::{error::WinFileInfoError, json::{self, JsonValue}, runner::CommandRunner};
+
+// folders that every windows user has, but not always in the same place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownFolder {
+    Desktop,
+    Documents,
+    Downloads,
+    AppData,
+    LocalAppData,
+    ProgramData,
+    ProgramFiles,
+    Temp
+}
+
+impl KnownFolder {
+    pub const ALL: [KnownFolder; 8] = [
+        KnownFolder::Desktop,
+        KnownFolder::Documents,
+        KnownFolder::Downloads,
+        KnownFolder::AppData,
+        KnownFolder::LocalAppData,
+        KnownFolder::ProgramData,
+        KnownFolder::ProgramFiles,
+        KnownFolder::Temp
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KnownFolder::Desktop => "Desktop",
+            KnownFolder::Documents => "Documents",
+            KnownFolder::Downloads => "Downloads",
+            KnownFolder::AppData => "AppData",
+            KnownFolder::LocalAppData => "LocalAppData",
+            KnownFolder::ProgramData => "ProgramData",
+            KnownFolder::ProgramFiles => "ProgramFiles",
+            KnownFolder::Temp => "Temp"
+        }
+    }
+
+    // where the folder is according to given environment. folders that were set by "with_known_folder()" win,
+    // then the variables which windows sets for them, then their default places under the user profile.
+    pub fn resolve(&self, environment: &Environment) -> Option<String> {
+        if let Some((_, path)) = environment.known_folders.iter().find(|(folder, _)| folder == self) {
+            return Some(path.clone());
+        }
+
+        let profile = || environment.get("USERPROFILE").map(|profile| profile.to_string()).or_else(|| {
+            Some(format!("{}{}", environment.get("HOMEDRIVE")?, environment.get("HOMEPATH")?))
+        });
+        let under = |base: Option<String>, rest: &str| base.map(|base| format!("{}\\{}", base.trim_end_matches('\\'), rest));
+        let variable = |name: &str| environment.get(name).map(|value| value.to_string());
+
+        match self {
+            KnownFolder::Desktop => under(profile(), "Desktop"),
+            KnownFolder::Documents => under(profile(), "Documents"),
+            KnownFolder::Downloads => under(profile(), "Downloads"),
+            KnownFolder::AppData => variable("APPDATA").or_else(|| under(profile(), "AppData\\Roaming")),
+            KnownFolder::LocalAppData => variable("LOCALAPPDATA").or_else(|| under(profile(), "AppData\\Local")),
+            KnownFolder::ProgramData => variable("ProgramData").or_else(|| variable("ALLUSERSPROFILE")).or_else(|| under(variable("SystemDrive"), "ProgramData")),
+            KnownFolder::ProgramFiles => variable("ProgramFiles").or_else(|| under(variable("SystemDrive"), "Program Files")),
+            KnownFolder::Temp => variable("TEMP").or_else(|| variable("TMP")).or_else(|| under(KnownFolder::LocalAppData.resolve(environment), "Temp"))
+        }
+    }
+}
+
+impl fmt::Display for KnownFolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for KnownFolder {
+    type Err = WinFileInfoError;
+
+    fn from_str(input: &str) -> Result<KnownFolder, WinFileInfoError> {
+        match KnownFolder::ALL.iter().find(|folder| folder.name().eq_ignore_ascii_case(input)) {
+            Some(folder) => Ok(*folder),
+            None => Err(WinFileInfoError::invalid_path(input, "it's not a known folder"))
+        }
+    }
+}
+
+// environment variables that the paths are expanded with, names are case insensitive like on windows. it's a
+// plain map instead of the process environment, so the paths of another machine or user can be expanded too.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Environment {
+    variables: Vec<(String, String)>,
+    known_folders: Vec<(KnownFolder, String)>
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment::default()
+    }
+
+    // variables of the current process.
+    pub fn from_process() -> Environment {
+        std::env::vars().fold(Environment::new(), |environment, (name, value)| environment.with_variable(&name, &value))
+    }
+
+    pub fn with_variable(mut self, name: &str, value: &str) -> Environment {
+        self.variables.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        self.variables.push((name.to_string(), value.to_string()));
+
+        self
+    }
+
+    // sets where a known folder is, for the folders which are redirected or moved to onedrive.
+    pub fn with_known_folder(mut self, folder: KnownFolder, path: &str) -> Environment {
+        self.known_folders.retain(|(existing, _)| *existing != folder);
+        self.known_folders.push((folder, path.to_string()));
+
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.variables.iter().find(|(existing, _)| existing.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn current_environment() -> Result<Environment, WinFileInfoError> {
+    current_environment_with(&crate::runner::PowerShellRunner)
+}
+
+// environment variables of powershell and the real places of the known folders, which windows knows even when
+// they are redirected or moved to onedrive.
+pub fn current_environment_with<R: CommandRunner>(runner: &R) -> Result<Environment, WinFileInfoError> {
+    let folders: Vec<String> = KnownFolder::ALL.iter().map(|folder| {
+        let expression = match folder {
+            KnownFolder::Downloads => "(New-Object -ComObject Shell.Application).Namespace('shell:Downloads').Self.Path".to_string(),
+            KnownFolder::Documents => "[Environment]::GetFolderPath('MyDocuments')".to_string(),
+            KnownFolder::AppData => "[Environment]::GetFolderPath('ApplicationData')".to_string(),
+            KnownFolder::LocalAppData => "[Environment]::GetFolderPath('LocalApplicationData')".to_string(),
+            KnownFolder::ProgramData => "[Environment]::GetFolderPath('CommonApplicationData')".to_string(),
+            KnownFolder::Temp => "[IO.Path]::GetTempPath().TrimEnd('\\')".to_string(),
+            folder => format!("[Environment]::GetFolderPath('{}')", folder.name())
+        };
+
+        format!("{} = {}", folder.name(), expression)
+    }).collect();
+
+    let command = format!("$variables = [ordered]@{{}}; Get-ChildItem Env: | ForEach-Object {{ $variables[$_.Name] = $_.Value }}; [ordered]@{{ Variables = $variables; {} }} | ConvertTo-Json -Compress -Depth 3", folders.join("; "));
+    let stdout = crate::run_query(runner, "Env:", &command)?;
+    let answer = json::parse(&stdout)?;
+    let mut environment = Environment::new();
+
+    if let Some(JsonValue::Object(variables)) = answer.get("Variables") {
+        for (name, value) in variables {
+            if let JsonValue::String(value) = value {
+                environment = environment.with_variable(name, value);
+            }
+        }
+    }
+
+    for folder in KnownFolder::ALL {
+        if let Some(JsonValue::String(path)) = answer.get(folder.name()) {
+            if !path.is_empty() {
+                environment = environment.with_known_folder(folder, path);
+            }
+        }
+    }
+
+    Ok(environment)
+}
+
+// expands "%VAR%", "$env:VAR", "${env:VAR}" and the "shell:Desktop" like known folders at the start of given
+// path. undefined variables are left in the path as they are, like cmd and powershell do, since names like
+// "50%off%.pdf" are legal file names. unknown folders are errors.
+//
+// let environment = Environment::new().with_variable("USERPROFILE", "D:\\Users\\necoo");
+//
+// assert_eq!(expand_path("%UserProfile%\\notes.txt", &environment).unwrap(), "D:\\Users\\necoo\\notes.txt");
+// assert_eq!(expand_path("shell:Desktop\\notes.txt", &environment).unwrap(), "D:\\Users\\necoo\\Desktop\\notes.txt");
+pub fn expand_path(path: &str, environment: &Environment) -> Result<String, WinFileInfoError> {
+    // the folder is not expanded again, a "%" in its path is a part of a name there.
+    let (mut expanded, mut rest) = match path.get(..6).is_some_and(|prefix| prefix.eq_ignore_ascii_case("shell:")) {
+        true => {
+            let (name, rest) = match path[6..].find(['\\', '/']) {
+                Some(index) => (&path[6..6 + index], &path[6 + index..]),
+                None => (&path[6..], "")
+            };
+            let folder: KnownFolder = name.parse().map_err(|_| WinFileInfoError::invalid_path(path, format!("'{}' is not a known folder", name)))?;
+
+            match folder.resolve(environment) {
+                Some(folder_path) => (folder_path, rest),
+                None => return Err(WinFileInfoError::invalid_path(path, format!("cannot find where '{}' is from the environment", folder)))
+            }
+        },
+        false => ("".to_string(), path)
+    };
+
+    while !rest.is_empty() {
+        let variable = variable_at(rest).and_then(|(name, length)| environment.get(name).map(|value| (value, length)));
+
+        let (value, length) = match variable {
+            Some(variable) => variable,
+            None => {
+                let character = rest.chars().next().unwrap_or_default();
+                expanded.push(character);
+                rest = &rest[character.len_utf8()..];
+
+                continue;
+            }
+        };
+
+        expanded.push_str(value);
+        rest = &rest[length..];
+    }
+
+    Ok(expanded)
+}
+
+// name and length of the variable at the start of given text, if there is one there.
+fn variable_at(text: &str) -> Option<(&str, usize)> {
+    if let Some(after) = text.strip_prefix('%') {
+        let end = after.find('%')?;
+
+        // names with spaces are not taken as variables, so "100% of 50%" stays as it is.
+        return match end == 0 || after[..end].contains(char::is_whitespace) {
+            true => None,
+            false => Some((&after[..end], end + 2))
+        };
+    }
+
+    if let Some(after) = text.strip_prefix("${") {
+        let end = after.find('}')?;
+        let name = after[..end].get(..4).filter(|prefix| prefix.eq_ignore_ascii_case("env:")).map(|_| &after[4..end])?;
+
+        return Some((name, end + 3));
+    }
+
+    let after = text.get(..5).filter(|prefix| prefix.eq_ignore_ascii_case("$env:")).map(|_| &text[5..])?;
+    let end = after.find(|character: char| !(character.is_ascii_alphanumeric() || character == '_')).unwrap_or(after.len());
+
+    match end {
+        0 => None,
+        _ => Some((&after[..end], end + 5))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::FixtureRunner;
+
+    fn environment() -> Environment {
+        Environment::new().with_variable("USERPROFILE", "D:\\Users\\necoo")
+                          .with_variable("SystemDrive", "D:")
+                          .with_variable("ProgramFiles(x86)", "D:\\Program Files (x86)")
+                          .with_variable("LOCALAPPDATA", "D:\\Users\\necoo\\AppData\\Local")
+    }
+
+    #[test]
+    fn test_expand_variables(){
+        let environment = environment();
+
+        assert_eq!(expand_path("%UserProfile%\\notes.txt", &environment).unwrap(), "D:\\Users\\necoo\\notes.txt");
+        assert_eq!(expand_path("$env:USERPROFILE\\notes.txt", &environment).unwrap(), "D:\\Users\\necoo\\notes.txt");
+        assert_eq!(expand_path("${env:ProgramFiles(x86)}\\Git", &environment).unwrap(), "D:\\Program Files (x86)\\Git");
+        assert_eq!(expand_path("%ProgramFiles(x86)%\\Git", &environment).unwrap(), "D:\\Program Files (x86)\\Git");
+        assert_eq!(expand_path("100% sure %% and 50%", &environment).unwrap(), "100% sure %% and 50%");
+        assert_eq!(expand_path("C:\\price$5", &environment).unwrap(), "C:\\price$5");
+        // undefined variables stay, "%_done%" and "%off%" are parts of the names here:
+        assert_eq!(expand_path("C:\\100%_done%.txt", &environment).unwrap(), "C:\\100%_done%.txt");
+        assert_eq!(expand_path("C:\\50%off%.pdf", &environment).unwrap(), "C:\\50%off%.pdf");
+        assert_eq!(expand_path("%MISSING%\\%UserProfile%", &environment).unwrap(), "%MISSING%\\D:\\Users\\necoo");
+        assert_eq!(expand_path("$env:MISSING\\a", &environment).unwrap(), "$env:MISSING\\a");
+    }
+
+    #[test]
+    fn test_known_folders(){
+        let environment = environment();
+
+        assert_eq!(KnownFolder::Desktop.resolve(&environment).unwrap(), "D:\\Users\\necoo\\Desktop");
+        assert_eq!(KnownFolder::AppData.resolve(&environment).unwrap(), "D:\\Users\\necoo\\AppData\\Roaming");
+        assert_eq!(KnownFolder::ProgramData.resolve(&environment).unwrap(), "D:\\ProgramData");
+        assert_eq!(KnownFolder::Temp.resolve(&environment).unwrap(), "D:\\Users\\necoo\\AppData\\Local\\Temp");
+        assert_eq!(KnownFolder::Desktop.resolve(&Environment::new()), None);
+
+        let redirected = environment.with_known_folder(KnownFolder::Desktop, "D:\\Users\\necoo\\OneDrive\\Desktop");
+
+        assert_eq!(expand_path("shell:desktop\\notes.txt", &redirected).unwrap(), "D:\\Users\\necoo\\OneDrive\\Desktop\\notes.txt");
+        assert_eq!(expand_path("shell:Downloads", &redirected).unwrap(), "D:\\Users\\necoo\\Downloads");
+        assert!(expand_path("shell:Pictures\\a.png", &redirected).is_err());
+        assert!(expand_path("shell:Desktop", &Environment::new()).is_err());
+    }
+
+    #[test]
+    fn test_current_environment_with_fixture(){
+        let runner = FixtureRunner::new().with_stdout("{\"Variables\":{\"USERPROFILE\":\"C:\\\\Users\\\\necoo\",\"TEMP\":\"C:\\\\Users\\\\necoo\\\\AppData\\\\Local\\\\Temp\"},\"Desktop\":\"C:\\\\Users\\\\necoo\\\\OneDrive\\\\Desktop\",\"Documents\":\"\"}");
+
+        let environment = current_environment_with(&runner).unwrap();
+
+        assert_eq!(environment.get("userprofile"), Some("C:\\Users\\necoo"));
+        assert_eq!(KnownFolder::Desktop.resolve(&environment).unwrap(), "C:\\Users\\necoo\\OneDrive\\Desktop");
+        assert_eq!(KnownFolder::Documents.resolve(&environment).unwrap(), "C:\\Users\\necoo\\Documents");
+        assert!(runner.commands()[0].contains("Desktop = [Environment]::GetFolderPath('Desktop')"));
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{acl::{AccessControlEntry, AceFlags, AceType, EntityAcl, FileSystemRights}, error::WinFileInfoError, sddl::SecurityDescriptor, sid::Sid};
+
+// the rights that icacls writes alone, like "(F)" or "(RX)".
+const SIMPLE_RIGHTS: [(&str, u32); 7] = [
+    ("N", 0x0),
+    ("F", 0x1F01FF),
+    ("M", 0x1301BF),
+    ("RX", 0x1200A9),
+    ("R", 0x120089),
+    ("W", 0x100116),
+    ("D", 0x10000)
+];
+
+// the rights that icacls writes as a list, like "(RD,WD,S)".
+const SPECIFIC_RIGHTS: [(&str, u32); 20] = [
+    ("DE", 0x10000),
+    ("RC", 0x20000),
+    ("WDAC", 0x40000),
+    ("WO", 0x80000),
+    ("S", 0x100000),
+    ("AS", 0x1000000),
+    ("MA", 0x2000000),
+    ("GR", 0x80000000),
+    ("GW", 0x40000000),
+    ("GE", 0x20000000),
+    ("GA", 0x10000000),
+    ("RD", 0x1),
+    ("WD", 0x2),
+    ("AD", 0x4),
+    ("REA", 0x8),
+    ("WEA", 0x10),
+    ("X", 0x20),
+    ("DC", 0x40),
+    ("RA", 0x80),
+    ("WA", 0x100)
+];
+
+const INHERITANCE: [(&str, AceFlags); 5] = [
+    ("OI", AceFlags::OBJECT_INHERIT),
+    ("CI", AceFlags::CONTAINER_INHERIT),
+    ("IO", AceFlags::INHERIT_ONLY),
+    ("NP", AceFlags::NO_PROPAGATE_INHERIT),
+    ("I", AceFlags::INHERITED)
+];
+
+// the acl of a path from an icacls output. the acls of the listings have only the aces, with the account names as
+// icacls writes them. the ones of the "/save" files have everything their sddl has, with the sids as trustees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IcaclsEntry {
+    pub path: String,
+    pub acl: EntityAcl
+}
+
+// the output of "icacls <path>", which can have many paths when it's run with wildcards or "/t":
+//
+// C:\Users\necoo\projects NT AUTHORITY\SYSTEM:(OI)(CI)(F)
+//                         BUILTIN\Users:(I)(OI)(CI)(RX)
+//                         Everyone:(DENY)(W)
+//
+// Successfully processed 1 files; Failed processing 0 files
+//
+// both the paths and the accounts can have spaces. the aces under the first one are indented up to the account,
+// which tells where the path ends. when there is a single ace, the account is taken as a well-known name or a
+// sid if it's one of them, then as the first "DOMAIN\name" one, and as the part after the last space otherwise.
+//
+// the summary and the errors of the paths that icacls couldn't read are in the language of windows, so the lines
+// which have no ace are skipped.
+pub fn parse_icacls_listing(output: &str) -> Result<Vec<IcaclsEntry>, WinFileInfoError> {
+    let lines: Vec<&str> = output.lines().map(|line| line.trim_end()).collect();
+    let mut entries = vec![];
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        index += 1;
+
+        if line.is_empty() || line.starts_with(char::is_whitespace) || ace_start(line).is_none() {
+            continue;
+        }
+
+        let mut continuations = vec![];
+
+        while index < lines.len() && lines[index].starts_with(char::is_whitespace) && ace_start(lines[index].trim()).is_some() {
+            continuations.push(lines[index]);
+            index += 1;
+        }
+
+        let indent = continuations.first().map(|continuation| continuation.chars().take_while(|character| character.is_whitespace()).count());
+        let (path, first_ace) = split_path(line, indent);
+        let mut access = vec![parse_listing_ace(first_ace).map_err(|reason| WinFileInfoError::unparseable(line, reason))?];
+
+        for continuation in continuations {
+            access.push(parse_listing_ace(continuation.trim()).map_err(|reason| WinFileInfoError::unparseable(continuation, reason))?);
+        }
+
+        entries.push(IcaclsEntry { path, acl: EntityAcl { access, ..EntityAcl::default() } });
+    }
+
+    Ok(entries)
+}
+
+// where the account of an ace line starts its permissions, the ":(" before "(OI)(CI)(F)". the path of a line
+// can't have it, since ":" is only allowed after the drive letter.
+fn ace_start(line: &str) -> Option<usize> {
+    match line.ends_with(')') {
+        true => line.find(":("),
+        false => None
+    }
+}
+
+fn split_path(line: &str, indent: Option<usize>) -> (String, &str) {
+    let characters: Vec<(usize, char)> = line.char_indices().collect();
+
+    // the column of the aces under it, if it's right after a space.
+    if let Some(indent) = indent {
+        if indent > 1 && indent < characters.len() && characters[indent - 1].1 == ' ' {
+            let start = characters[indent].0;
+
+            return (line[..start].trim_end().to_string(), &line[start..]);
+        }
+    }
+
+    let head = &line[..ace_start(line).unwrap_or(line.len())];
+    let spaces: Vec<usize> = head.char_indices().filter(|(_, character)| *character == ' ').map(|(index, _)| index).collect();
+
+    let known = spaces.iter().find(|space| {
+        let account = &head[*space + 1..];
+
+        Sid::from_well_known_name(account).is_some() || Sid::parse(account).is_ok()
+    });
+
+    // "CONTOSO\Domain Users", the domain has no spaces and the name has no more backslashes.
+    let domain = spaces.iter().find(|space| {
+        let account = &head[*space + 1..];
+
+        match account.split_once('\\') {
+            Some((domain, name)) => !domain.is_empty() && !domain.contains([' ', '/']) && !name.contains(['\\', '/']),
+            None => false
+        }
+    });
+
+    match known.or(domain).or(spaces.last()) {
+        Some(space) => (line[..*space].to_string(), &line[*space + 1..]),
+        None => ("".to_string(), line)
+    }
+}
+
+// "BUILTIN\Users:(I)(OI)(CI)(RX)".
+fn parse_listing_ace(ace: &str) -> Result<AccessControlEntry, String> {
+    let start = ace_start(ace).ok_or(format!("'{}' is not an ace", ace))?;
+    let trustee = ace[..start].trim();
+    let groups = &ace[start + 2..ace.len() - 1];
+    let mut ace_type = AceType::Allow;
+    let mut flags = AceFlags::empty();
+    let mut rights = FileSystemRights::empty();
+
+    for group in groups.split(")(") {
+        if group.eq_ignore_ascii_case("DENY") {
+            ace_type = AceType::Deny;
+            continue;
+        }
+
+        if let Some((_, flag)) = INHERITANCE.iter().find(|(name, _)| name.eq_ignore_ascii_case(group)) {
+            flags.insert(*flag);
+            continue;
+        }
+
+        for right in group.split(',').map(|right| right.trim()) {
+            let bits = SIMPLE_RIGHTS.iter().chain(SPECIFIC_RIGHTS.iter()).find(|(name, _)| name.eq_ignore_ascii_case(right)).map(|(_, bits)| *bits);
+
+            match bits {
+                Some(bits) => rights.insert(FileSystemRights::from_bits_retain(bits)),
+                None => return Err(format!("'{}' is not an icacls permission", right))
+            }
+        }
+    }
+
+    if trustee.is_empty() {
+        return Err(format!("ace '{}' has no account", ace));
+    }
+
+    Ok(AccessControlEntry::new(ace_type, trustee, rights, flags))
+}
+
+// a file of "icacls <folder>\* /save <file> /t": a line with the path of every entity relative to the folder,
+// and a line with its sddl after it. icacls writes it in utf-16le, utf-8 files are accepted too for the ones which
+// were converted.
+pub fn parse_icacls_save(bytes: &[u8]) -> Result<Vec<IcaclsEntry>, WinFileInfoError> {
+    let utf16 = bytes.starts_with(&[0xFF, 0xFE]) || (bytes.len() >= 2 && bytes.len().is_multiple_of(2) && bytes[0] != 0 && bytes[1] == 0);
+
+    let text = match utf16 {
+        true => {
+            let units: Vec<u16> = bytes.chunks(2).map(|pair| u16::from_le_bytes([pair[0], *pair.get(1).unwrap_or(&0)])).collect();
+
+            String::from_utf16(&units).map_err(|_| WinFileInfoError::NonUtf8Output { stderr: None })?
+        },
+        false => String::from_utf8(bytes.to_vec()).map_err(|_| WinFileInfoError::NonUtf8Output { stderr: None })?
+    };
+
+    let lines: Vec<&str> = text.trim_start_matches('\u{FEFF}').lines().map(|line| line.trim_end()).filter(|line| !line.is_empty()).collect();
+
+    if !lines.len().is_multiple_of(2) {
+        return Err(WinFileInfoError::unparseable(lines.last().copied().unwrap_or(""), "icacls file has a path without its sddl"));
+    }
+
+    lines.chunks(2).map(|pair| Ok(IcaclsEntry {
+        path: pair[0].to_string(),
+        acl: SecurityDescriptor::parse(pair[1])?.to_entity_acl()
+    })).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICACLS_LISTING: &str = include_str!("../tests/fixtures/icacls_listing.txt");
+    const ICACLS_SAVE: &[u8] = include_bytes!("../tests/fixtures/icacls_save.bin");
+
+    #[test]
+    fn test_listing(){
+        let entries = parse_icacls_listing(ICACLS_LISTING).unwrap();
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].path, "C:\\Users\\necoo\\My Projects");
+        assert_eq!(entries[0].acl.access.len(), 6);
+
+        let access = &entries[0].acl.access;
+
+        assert_eq!(access[0], AccessControlEntry::new(AceType::Deny, "DESKTOP-4SJ1N0T\\guest", FileSystemRights::from_bits_retain(0x110116), AceFlags::OBJECT_INHERIT | AceFlags::CONTAINER_INHERIT));
+        assert_eq!(access[1].trustee, "NT AUTHORITY\\SYSTEM");
+        assert_eq!(access[1].rights, FileSystemRights::FULL_CONTROL);
+        assert!(access[1].is_inherited());
+        assert_eq!(access[3].trustee, "CREATOR OWNER");
+        assert_eq!(access[3].flags, AceFlags::OBJECT_INHERIT | AceFlags::CONTAINER_INHERIT | AceFlags::INHERIT_ONLY | AceFlags::INHERITED);
+        assert_eq!(access[3].rights, FileSystemRights::GENERIC_ALL);
+        assert_eq!(access[4].rights.to_string(), "ReadAndExecute, Synchronize");
+        assert_eq!(access[5].trustee, "S-1-5-21-3623811015-3361044348-30300820-1013");
+        assert_eq!(access[5].rights, FileSystemRights::from_bits_retain(0x1200A9 | 0x100116));
+
+        // a single ace, the path and the account both have spaces:
+        assert_eq!(entries[1].path, "D:\\shares\\finance\\budget 2026.xlsx");
+        assert_eq!(entries[1].acl.access[0].trustee, "NT AUTHORITY\\Authenticated Users");
+        assert_eq!(entries[1].acl.access[0].rights, FileSystemRights::MODIFY | FileSystemRights::SYNCHRONIZE);
+
+        assert_eq!(entries[2].path, "D:\\shares\\finance\\Q3 report.pdf");
+        assert_eq!(entries[2].acl.access[0].trustee, "CONTOSO\\accountants");
+        assert_eq!(entries[2].acl.access[0].rights, FileSystemRights::READ_DATA | FileSystemRights::READ_ATTRIBUTES | FileSystemRights::SYNCHRONIZE);
+
+        // a domain account with a space:
+        assert_eq!(entries[3].path, "D:\\shares\\finance\\Q4 report.pdf");
+        assert_eq!(entries[3].acl.access[0].trustee, "CONTOSO\\Domain Users");
+        assert_eq!(entries[3].acl.access[0].rights, FileSystemRights::from_bits_retain(0x120089));
+    }
+
+    #[test]
+    fn test_listing_errors(){
+        assert!(parse_icacls_listing("").unwrap().is_empty());
+        assert!(parse_icacls_listing("C:\\x: Access is denied.\r\nSuccessfully processed 0 files; Failed processing 1 files").unwrap().is_empty());
+        assert!(matches!(parse_icacls_listing("C:\\x Everyone:(OI)(SHINY)"), Err(WinFileInfoError::UnparseableRecord { .. })));
+        assert!(matches!(parse_icacls_listing("C:\\x Everyone:(F)\r\n  Everyone:(Q)"), Err(WinFileInfoError::UnparseableRecord { .. })));
+    }
+
+    #[test]
+    fn test_save_file(){
+        let entries = parse_icacls_save(ICACLS_SAVE).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].path, "projects");
+        assert_eq!(entries[0].acl.sddl, "D:AI(A;OICIID;FA;;;SY)(A;OICIID;FA;;;BA)(A;OICIIOID;GA;;;CO)(A;OICIID;0x1200a9;;;BU)");
+        assert_eq!(entries[0].acl.access[0].trustee, "S-1-5-18");
+        assert_eq!(entries[1].path, "projects\\notes über.txt");
+        assert!(entries[1].acl.access_protected);
+        assert_eq!(entries[1].acl.access[0].ace_type, AceType::Deny);
+        assert_eq!(entries[2].acl.access.len(), 1);
+
+        // the same file after a conversion to utf-8:
+        let units: Vec<u16> = ICACLS_SAVE.chunks(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+
+        assert_eq!(parse_icacls_save(String::from_utf16(&units).unwrap().as_bytes()).unwrap(), entries);
+        assert!(matches!(parse_icacls_save("projects\r\n".as_bytes()), Err(WinFileInfoError::UnparseableRecord { .. })));
+        assert!(matches!(parse_icacls_save("projects\r\nD:(A;;FA;;;NOBODY)\r\n".as_bytes()), Err(WinFileInfoError::UnparseableRecord { .. })));
+    }
+}
@@ -0,0 +1,322 @@
+use std::fmt;
+
+use crate::error::WinFileInfoError;
+
+// minimal json parser for the output of powershell's "ConvertTo-Json". numbers are kept as their source text,
+// so big integers like file sizes don't lose precision when they pass through "f64".
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>)
+}
+
+impl JsonValue {
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None
+        }
+    }
+}
+
+// writes the value back as compact json, used for showing the records that we couldn't read.
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(value) => write!(f, "{}", value),
+            JsonValue::Number(value) => write!(f, "{}", value),
+            JsonValue::String(value) => write_string(f, value),
+            JsonValue::Array(items) => {
+                write!(f, "[")?;
+
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}", item)?;
+                }
+
+                write!(f, "]")
+            },
+            JsonValue::Object(fields) => {
+                write!(f, "{{")?;
+
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for character in value.chars() {
+        match character {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            character if (character as u32) < 0x20 => write!(f, "\\u{:04x}", character as u32)?,
+            character => write!(f, "{}", character)?
+        }
+    }
+
+    write!(f, "\"")
+}
+
+pub fn parse(input: &str) -> Result<JsonValue, WinFileInfoError> {
+    let mut parser = Parser { chars: input.trim_start_matches('\u{feff}').chars().collect(), position: 0 };
+
+    parser.skip_whitespace();
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+
+    match parser.peek() {
+        None => Ok(value),
+        Some(_) => Err(parser.error("unexpected characters after the json value"))
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let character = self.peek();
+        self.position += 1;
+
+        character
+    }
+
+    fn error(&self, message: &str) -> WinFileInfoError {
+        WinFileInfoError::unparseable(self.chars.iter().collect::<String>(), format!("invalid json at character {}: {}", self.position, message))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\r' | '\n') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), WinFileInfoError> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(self.error(&format!("expected '{}'", word)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, WinFileInfoError> {
+        match self.peek() {
+            Some('{') => self.parse_object(),
+            Some('[') => self.parse_array(),
+            Some('"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some('t') => self.expect("true").map(|_| JsonValue::Bool(true)),
+            Some('f') => self.expect("false").map(|_| JsonValue::Bool(false)),
+            Some('n') => self.expect("null").map(|_| JsonValue::Null),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input"))
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, WinFileInfoError> {
+        let mut fields = vec![];
+        self.position += 1;
+        self.skip_whitespace();
+
+        if self.peek() == Some('}') {
+            self.position += 1;
+
+            return Ok(JsonValue::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a field name"));
+            }
+
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            self.skip_whitespace();
+            fields.push((key, self.parse_value()?));
+            self.skip_whitespace();
+
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(JsonValue::Object(fields)),
+                _ => return Err(self.error("expected ',' or '}'"))
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, WinFileInfoError> {
+        let mut items = vec![];
+        self.position += 1;
+        self.skip_whitespace();
+
+        if self.peek() == Some(']') {
+            self.position += 1;
+
+            return Ok(JsonValue::Array(items));
+        }
+
+        loop {
+            self.skip_whitespace();
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(JsonValue::Array(items)),
+                _ => return Err(self.error("expected ',' or ']'"))
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, WinFileInfoError> {
+        let start = self.position;
+
+        while let Some('-' | '+' | '.' | 'e' | 'E' | '0'..='9') = self.peek() {
+            self.position += 1;
+        }
+
+        let number: String = self.chars[start..self.position].iter().collect();
+
+        match number.parse::<f64>() {
+            Ok(_) => Ok(JsonValue::Number(number)),
+            Err(_) => Err(self.error("invalid number"))
+        }
+    }
+
+    fn parse_hex_escape(&mut self) -> Result<u32, WinFileInfoError> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            match self.next().and_then(|character| character.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(self.error("invalid unicode escape"))
+            }
+        }
+
+        Ok(code)
+    }
+
+    fn parse_string(&mut self) -> Result<String, WinFileInfoError> {
+        let mut result = String::new();
+        self.position += 1;
+
+        loop {
+            match self.next() {
+                Some('"') => return Ok(result),
+                Some('\\') => match self.next() {
+                    Some('"') => result.push('"'),
+                    Some('\\') => result.push('\\'),
+                    Some('/') => result.push('/'),
+                    Some('b') => result.push('\u{8}'),
+                    Some('f') => result.push('\u{c}'),
+                    Some('n') => result.push('\n'),
+                    Some('r') => result.push('\r'),
+                    Some('t') => result.push('\t'),
+                    Some('u') => {
+                        let code = self.parse_hex_escape()?;
+
+                        // characters outside of the basic plane come as utf-16 surrogate pairs.
+                        let code = if (0xD800..0xDC00).contains(&code) {
+                            self.expect("\\u")?;
+                            let low = self.parse_hex_escape()?;
+
+                            if !(0xDC00..0xE000).contains(&low) {
+                                return Err(self.error("invalid surrogate pair"));
+                            }
+
+                            0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+                        } else {
+                            code
+                        };
+
+                        match char::from_u32(code) {
+                            Some(character) => result.push(character),
+                            None => return Err(self.error("invalid unicode escape"))
+                        }
+                    },
+                    _ => return Err(self.error("invalid escape sequence"))
+                },
+                Some(character) => result.push(character),
+                None => return Err(self.error("unterminated string"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_object_and_array(){
+        let value = parse("[{\"Name\":\"a\",\"Length\":5368709120,\"Owner\":null,\"Hidden\":true},{}]").unwrap();
+
+        match value {
+            JsonValue::Array(items) => {
+                assert_eq!(items.len(), 2);
+                assert_eq!(items[0].get("Name"), Some(&JsonValue::String("a".to_string())));
+                assert_eq!(items[0].get("Length"), Some(&JsonValue::Number("5368709120".to_string())));
+                assert_eq!(items[0].get("Owner"), Some(&JsonValue::Null));
+                assert_eq!(items[0].get("Hidden"), Some(&JsonValue::Bool(true)));
+                assert_eq!(items[1], JsonValue::Object(vec![]));
+            },
+            _ => panic!("expected an array")
+        }
+    }
+
+    #[test]
+    fn test_parse_escapes(){
+        let value = parse("\"C:\\\\Users\\u0027s \\ud83d\\ude00 \\\"x\\\"\"").unwrap();
+
+        assert_eq!(value, JsonValue::String("C:\\Users's \u{1F600} \"x\"".to_string()));
+    }
+
+    #[test]
+    fn test_display(){
+        let input = "{\"Name\":\"a \\\"b\\\"\\n\",\"Items\":[1,null,true,{}]}";
+
+        assert_eq!(parse(input).unwrap().to_string(), input);
+    }
+
+    #[test]
+    fn test_parse_errors(){
+        assert!(parse("").is_err());
+        assert!(parse("{\"Name\":}").is_err());
+        assert!(parse("{\"Name\":\"a\"").is_err());
+        assert!(parse("[1, 2] 3").is_err());
+        assert!(parse("\"\\ud83d\"").is_err());
+    }
+}
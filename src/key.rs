@@ -0,0 +1,307 @@
+use std::{cmp::Ordering, fmt, hash::{Hash, Hasher}};
+
+use crate::path::WindowsPath;
+
+// a path that compares, hashes and sorts like ntfs sees it, so "C:\Users\necoo", "c:/users/NECOO/" and
+// "\\?\C:\Users\necoo" are the same key. the original path is kept for showing it:
+//
+// let mut seen = HashSet::new();
+//
+// assert!(seen.insert(WindowsPathKey::new("C:\\Users\\necoo")));
+// assert!(!seen.insert(WindowsPathKey::new("c:/users/NECOO/")));
+//
+// the components are not resolved, "C:\a\..\b" and "C:\b" are different keys since ".." may pass a link.
+#[derive(Debug, Clone)]
+pub struct WindowsPathKey {
+    original: String,
+    // upper-cased utf-16 units of the normalized path, every comparison uses only them.
+    folded: Vec<u16>
+}
+
+impl WindowsPathKey {
+    pub fn new(path: &str) -> WindowsPathKey {
+        // the paths that can't be parsed still get a key, with their separators unified.
+        let normalized = match WindowsPath::parse(path) {
+            Ok(parsed) if parsed.is_verbatim() => parsed.to_win32().unwrap_or(parsed).to_string(),
+            Ok(parsed) => parsed.to_string(),
+            Err(_) => path.replace('/', "\\")
+        };
+
+        WindowsPathKey {
+            original: path.to_string(),
+            folded: normalized.encode_utf16().map(upcase).collect()
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.original
+    }
+
+    // the normalized and upper-cased form which the key is compared with.
+    pub fn folded(&self) -> String {
+        String::from_utf16_lossy(&self.folded)
+    }
+}
+
+// the "$UpCase" table that windows 7 and the later versions write when they format a volume, as runs of
+// "(first, last, difference, step)". every unit from "first" to "last", "step" by "step", is upper-cased by adding
+// "difference" to it, the units which are in no run stay as they are. it's frozen at the unicode of that time,
+// so the letters that unicode gave a case later, like the georgian mtavruli or the cherokee small letters, are
+// not upper-cased and "µ", "ı" or "ſ" don't become "Μ", "I" or "S" like the simple mappings of unicode say.
+//
+// the table of a volume which was formatted by windows xp or vista is a little different, but it's the one of
+// the volume that matters and the old ones are rare now.
+const UPCASE_RUNS: [(u16, u16, i16, u16); 134] = [
+    (0x0061, 0x007A, -32, 1),
+    (0x00E0, 0x00F6, -32, 1),
+    (0x00F8, 0x00FE, -32, 1),
+    (0x00FF, 0x00FF, 121, 1),
+    (0x0101, 0x012F, -1, 2),
+    (0x0133, 0x0137, -1, 2),
+    (0x013A, 0x0148, -1, 2),
+    (0x014B, 0x0177, -1, 2),
+    (0x017A, 0x017E, -1, 2),
+    (0x0180, 0x0180, 195, 1),
+    (0x0183, 0x0185, -1, 2),
+    (0x0188, 0x0188, -1, 1),
+    (0x018C, 0x018C, -1, 1),
+    (0x0192, 0x0192, -1, 1),
+    (0x0195, 0x0195, 97, 1),
+    (0x0199, 0x0199, -1, 1),
+    (0x019A, 0x019A, 163, 1),
+    (0x019E, 0x019E, 130, 1),
+    (0x01A1, 0x01A5, -1, 2),
+    (0x01A8, 0x01A8, -1, 1),
+    (0x01AD, 0x01AD, -1, 1),
+    (0x01B0, 0x01B0, -1, 1),
+    (0x01B4, 0x01B6, -1, 2),
+    (0x01B9, 0x01B9, -1, 1),
+    (0x01BD, 0x01BD, -1, 1),
+    (0x01BF, 0x01BF, 56, 1),
+    (0x01C6, 0x01C6, -2, 1),
+    (0x01C9, 0x01C9, -2, 1),
+    (0x01CC, 0x01CC, -2, 1),
+    (0x01CE, 0x01DC, -1, 2),
+    (0x01DD, 0x01DD, -79, 1),
+    (0x01DF, 0x01EF, -1, 2),
+    (0x01F3, 0x01F3, -2, 1),
+    (0x01F5, 0x01F5, -1, 1),
+    (0x01F9, 0x021F, -1, 2),
+    (0x0223, 0x0233, -1, 2),
+    (0x023C, 0x023C, -1, 1),
+    (0x0242, 0x0242, -1, 1),
+    (0x0247, 0x024F, -1, 2),
+    (0x0250, 0x0250, 10783, 1),
+    (0x0251, 0x0251, 10780, 1),
+    (0x0253, 0x0253, -210, 1),
+    (0x0254, 0x0254, -206, 1),
+    (0x0256, 0x0257, -205, 1),
+    (0x0259, 0x0259, -202, 1),
+    (0x025B, 0x025B, -203, 1),
+    (0x0260, 0x0260, -205, 1),
+    (0x0263, 0x0263, -207, 1),
+    (0x0268, 0x0268, -209, 1),
+    (0x0269, 0x0269, -211, 1),
+    (0x026B, 0x026B, 10743, 1),
+    (0x026F, 0x026F, -211, 1),
+    (0x0271, 0x0271, 10749, 1),
+    (0x0272, 0x0272, -213, 1),
+    (0x0275, 0x0275, -214, 1),
+    (0x027D, 0x027D, 10727, 1),
+    (0x0280, 0x0280, -218, 1),
+    (0x0283, 0x0283, -218, 1),
+    (0x0288, 0x0288, -218, 1),
+    (0x0289, 0x0289, -69, 1),
+    (0x028A, 0x028B, -217, 1),
+    (0x028C, 0x028C, -71, 1),
+    (0x0292, 0x0292, -219, 1),
+    (0x0371, 0x0373, -1, 2),
+    (0x0377, 0x0377, -1, 1),
+    (0x037B, 0x037D, 130, 1),
+    (0x03AC, 0x03AC, -38, 1),
+    (0x03AD, 0x03AF, -37, 1),
+    (0x03B1, 0x03C1, -32, 1),
+    (0x03C3, 0x03CB, -32, 1),
+    (0x03CC, 0x03CC, -64, 1),
+    (0x03CD, 0x03CE, -63, 1),
+    (0x03D7, 0x03D7, -8, 1),
+    (0x03D9, 0x03EF, -1, 2),
+    (0x03F2, 0x03F2, 7, 1),
+    (0x03F8, 0x03F8, -1, 1),
+    (0x03FB, 0x03FB, -1, 1),
+    (0x0430, 0x044F, -32, 1),
+    (0x0450, 0x045F, -80, 1),
+    (0x0461, 0x0481, -1, 2),
+    (0x048B, 0x04BF, -1, 2),
+    (0x04C2, 0x04CE, -1, 2),
+    (0x04CF, 0x04CF, -15, 1),
+    (0x04D1, 0x0523, -1, 2),
+    (0x0561, 0x0586, -48, 1),
+    (0x1D79, 0x1D79, -30204, 1),
+    (0x1D7D, 0x1D7D, 3814, 1),
+    (0x1E01, 0x1E95, -1, 2),
+    (0x1EA1, 0x1EFF, -1, 2),
+    (0x1F00, 0x1F07, 8, 1),
+    (0x1F10, 0x1F15, 8, 1),
+    (0x1F20, 0x1F27, 8, 1),
+    (0x1F30, 0x1F37, 8, 1),
+    (0x1F40, 0x1F45, 8, 1),
+    (0x1F51, 0x1F57, 8, 2),
+    (0x1F60, 0x1F67, 8, 1),
+    (0x1F70, 0x1F71, 74, 1),
+    (0x1F72, 0x1F75, 86, 1),
+    (0x1F76, 0x1F77, 100, 1),
+    (0x1F78, 0x1F79, 128, 1),
+    (0x1F7A, 0x1F7B, 112, 1),
+    (0x1F7C, 0x1F7D, 126, 1),
+    (0x1F80, 0x1F87, 8, 1),
+    (0x1F90, 0x1F97, 8, 1),
+    (0x1FA0, 0x1FA7, 8, 1),
+    (0x1FB0, 0x1FB1, 8, 1),
+    (0x1FB3, 0x1FB3, 9, 1),
+    (0x1FC3, 0x1FC3, 9, 1),
+    (0x1FD0, 0x1FD1, 8, 1),
+    (0x1FE0, 0x1FE1, 8, 1),
+    (0x1FE5, 0x1FE5, 7, 1),
+    (0x1FF3, 0x1FF3, 9, 1),
+    (0x214E, 0x214E, -28, 1),
+    (0x2170, 0x217F, -16, 1),
+    (0x2184, 0x2184, -1, 1),
+    (0x24D0, 0x24E9, -26, 1),
+    (0x2C30, 0x2C5E, -48, 1),
+    (0x2C61, 0x2C61, -1, 1),
+    (0x2C65, 0x2C65, -10795, 1),
+    (0x2C66, 0x2C66, -10792, 1),
+    (0x2C68, 0x2C6C, -1, 2),
+    (0x2C73, 0x2C73, -1, 1),
+    (0x2C76, 0x2C76, -1, 1),
+    (0x2C81, 0x2CE3, -1, 2),
+    (0x2D00, 0x2D25, -7264, 1),
+    (0xA641, 0xA65F, -1, 2),
+    (0xA663, 0xA66D, -1, 2),
+    (0xA681, 0xA697, -1, 2),
+    (0xA723, 0xA72F, -1, 2),
+    (0xA733, 0xA76F, -1, 2),
+    (0xA77A, 0xA77C, -1, 2),
+    (0xA77F, 0xA787, -1, 2),
+    (0xA78C, 0xA78C, -1, 1),
+    (0xFF41, 0xFF5A, -32, 1)
+];
+
+// ntfs upper-cases every utf-16 unit on its own with its upcase table, without the rules of any locale. so "ß"
+// stays "ß" instead of becoming "SS" and surrogate pairs are never changed.
+pub(crate) fn upcase(unit: u16) -> u16 {
+    let index = UPCASE_RUNS.partition_point(|(first, _, _, _)| *first <= unit);
+
+    match index.checked_sub(1).map(|index| UPCASE_RUNS[index]) {
+        Some((first, last, difference, step)) if unit <= last && (unit - first).is_multiple_of(step) => unit.wrapping_add_signed(difference),
+        _ => unit
+    }
+}
+
+impl PartialEq for WindowsPathKey {
+    fn eq(&self, other: &WindowsPathKey) -> bool {
+        self.folded == other.folded
+    }
+}
+
+impl Eq for WindowsPathKey {}
+
+impl Hash for WindowsPathKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.folded.hash(state);
+    }
+}
+
+// ordinal order of the upper-cased utf-16 units, the order ntfs keeps the names of a directory in.
+impl Ord for WindowsPathKey {
+    fn cmp(&self, other: &WindowsPathKey) -> Ordering {
+        self.folded.cmp(&other.folded)
+    }
+}
+
+impl PartialOrd for WindowsPathKey {
+    fn partial_cmp(&self, other: &WindowsPathKey) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for WindowsPathKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.original)
+    }
+}
+
+impl From<&str> for WindowsPathKey {
+    fn from(path: &str) -> WindowsPathKey {
+        WindowsPathKey::new(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashSet};
+
+    #[test]
+    fn test_same_paths(){
+        let key = WindowsPathKey::new("C:\\Users\\necoo\\Desktop");
+
+        for same in ["c:\\users\\NECOO\\desktop", "C:/Users/necoo/Desktop/", "C:\\Users\\\\necoo\\Desktop\\\\", "\\\\?\\C:\\Users\\necoo\\Desktop", "\\\\?\\c:\\USERS\\necoo\\desktop"] {
+            assert_eq!(WindowsPathKey::new(same), key, "{}", same);
+        }
+
+        assert_eq!(WindowsPathKey::new("\\\\?\\UNC\\Server\\Share\\a"), WindowsPathKey::new("//server/share/A/"));
+        assert_eq!(WindowsPathKey::new("ÇALIŞMA\\Ünite.txt"), WindowsPathKey::new("çalişma/ünite.TXT"));
+        assert_eq!(WindowsPathKey::new("C:\\"), WindowsPathKey::new("c:/"));
+        assert_eq!(key.as_str(), "C:\\Users\\necoo\\Desktop");
+        assert_eq!(key.folded(), "C:\\USERS\\NECOO\\DESKTOP");
+    }
+
+    #[test]
+    fn test_different_paths(){
+        for (left, right) in [
+            ("C:\\a\\..\\b", "C:\\b"),
+            ("C:\\a", "C:a"),
+            ("C:\\Straße", "C:\\STRASSE"),
+            ("C:\\ı", "C:\\I"),
+            ("C:\\ſ", "C:\\S"),
+            ("C:\\µ", "C:\\Μ"),
+            // the letters which got their cases after the table was made:
+            ("C:\\\u{10D0}", "C:\\\u{1C90}"),
+            ("C:\\\u{AB70}", "C:\\\u{13A0}"),
+            ("C:\\\u{A7C1}", "C:\\\u{A7C0}"),
+            ("C:\\a", "D:\\a"),
+            ("\\\\?\\Volume{1}\\a", "\\\\?\\Volume{2}\\a")
+        ] {
+            assert_ne!(WindowsPathKey::new(left), WindowsPathKey::new(right), "{} {}", left, right);
+        }
+    }
+
+    #[test]
+    fn test_upcase_table(){
+        let upcased = |text: &str| String::from_utf16(&text.encode_utf16().map(upcase).collect::<Vec<u16>>()).unwrap();
+
+        assert_eq!(upcased("abcxyz{~àöø÷þÿ"), "ABCXYZ{~ÀÖØ÷ÞŸ");
+        assert_eq!(upcased("ğüşiçöıſµß"), "ĞÜŞIÇÖıſµß");
+        assert_eq!(upcased("αβγσςάώ"), "ΑΒΓΣςΆΏ");
+        assert_eq!(upcased("привіт ёђџ"), "ПРИВІТ ЁЂЏ");
+        assert_eq!(upcased("ⴀⴥ ⰰⱞ ǆǉǌ ꙁꞌ ᵹ"), "ႠჅ ⰀⰮ ǄǇǊ ꙀꞋ Ᵹ");
+        assert_eq!(upcased("ａｚ ⅰⅿ ⓐⓩ"), "ＡＺ ⅠⅯ ⒶⓏ");
+        // the titlecase digraphs and the letters which have no upper case in the table stay:
+        assert_eq!(upcased("ǅǈǋ ა ꭰ ꟁ 😀"), "ǅǈǋ ა ꭰ ꟁ 😀");
+    }
+
+    #[test]
+    fn test_collections(){
+        let paths = ["C:\\Users\\necoo\\b.txt", "c:/users/necoo/B.TXT", "C:\\Users\\necoo\\a.txt", "\\\\?\\C:\\Users\\necoo\\A.txt", "C:\\Users\\necoo\\_.txt"];
+
+        let unique: HashSet<WindowsPathKey> = paths.iter().map(|path| WindowsPathKey::new(path)).collect();
+        let sorted: BTreeSet<WindowsPathKey> = paths.iter().map(|path| WindowsPathKey::new(path)).collect();
+        let sorted: Vec<String> = sorted.iter().map(|key| key.folded()).collect();
+
+        assert_eq!(unique.len(), 3);
+        // "A" < "B" < "_" once they are upper-cased, like in an ntfs directory:
+        assert_eq!(sorted, ["C:\\USERS\\NECOO\\A.TXT", "C:\\USERS\\NECOO\\B.TXT", "C:\\USERS\\NECOO\\_.TXT"]);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::attributes::FileAttributes;
+
+// what an entity is, built from its attributes. in windows an entity can be a directory, an archive and a reparse
+// point or symlink in the same time, so the predicates are meant to be combined:
+//
+// let kind = entity_kind("C:\\Users\\necoo\\OneDrive").unwrap();
+//
+// if kind.is_dir() && kind.is_reparse() { ... }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct EntityKind {
+    attributes: FileAttributes
+}
+
+impl EntityKind {
+    pub fn from_attributes(attributes: FileAttributes) -> EntityKind {
+        EntityKind { attributes }
+    }
+
+    pub fn attributes(&self) -> FileAttributes {
+        self.attributes
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.attributes.contains(FileAttributes::DIRECTORY)
+    }
+
+    pub fn is_file(&self) -> bool {
+        !self.is_dir()
+    }
+
+    pub fn is_archive(&self) -> bool {
+        self.attributes.contains(FileAttributes::ARCHIVE)
+    }
+
+    // reparse points are symlinks, junctions, mount points and the placeholders of cloud files like onedrive's.
+    pub fn is_reparse(&self) -> bool {
+        self.attributes.contains(FileAttributes::REPARSE_POINT)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.attributes.contains(FileAttributes::READONLY)
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.attributes.contains(FileAttributes::HIDDEN)
+    }
+
+    pub fn is_system(&self) -> bool {
+        self.attributes.contains(FileAttributes::SYSTEM)
+    }
+
+    // true if the entity has all of the given attributes.
+    pub fn has(&self, attributes: FileAttributes) -> bool {
+        self.attributes.contains(attributes)
+    }
+}
+
+impl From<FileAttributes> for EntityKind {
+    fn from(attributes: FileAttributes) -> EntityKind {
+        EntityKind::from_attributes(attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_predicates(){
+        let kind = EntityKind::from_attributes(FileAttributes::DIRECTORY | FileAttributes::ARCHIVE | FileAttributes::REPARSE_POINT);
+
+        assert!(kind.is_dir() && kind.is_archive() && kind.is_reparse());
+        assert!(!kind.is_file());
+        assert!(!kind.is_hidden() && !kind.is_system() && !kind.is_read_only());
+        assert!(kind.has(FileAttributes::DIRECTORY | FileAttributes::REPARSE_POINT));
+        assert!(!kind.has(FileAttributes::DIRECTORY | FileAttributes::HIDDEN));
+    }
+
+    #[test]
+    fn test_file_kind(){
+        let kind = EntityKind::from(FileAttributes::ARCHIVE | FileAttributes::READONLY);
+
+        assert!(kind.is_file() && kind.is_archive() && kind.is_read_only());
+        assert_eq!(kind.attributes(), FileAttributes::ARCHIVE | FileAttributes::READONLY);
+    }
+}
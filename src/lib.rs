@@ -1,345 +1,693 @@
-use std::{process::{Command, Output}, str::from_utf8, io::Error, env::current_dir};
+use std::{collections::HashMap, io::{Error, ErrorKind}, env::current_dir};
 
 #[cfg(target_os = "windows")]
+use std::process::Command;
+
+mod json;
+pub mod access;
+pub mod acl;
+pub mod attributes;
+pub mod error;
+pub mod expand;
+pub mod icacls;
+pub mod key;
+pub mod kind;
+pub mod long_path;
+pub mod native;
+pub mod path;
+pub mod quote;
+pub mod runner;
+pub mod sddl;
+pub mod session;
+pub mod short_name;
+pub mod sid;
+pub mod timestamp;
+pub mod translate;
+pub mod validation;
+pub mod wildcard;
+
+pub use access::effective_access;
+pub use acl::{entity_acl, entity_acl_with, AccessControlEntry, AceFlags, AceType, EntityAcl, FileSystemRights};
+pub use attributes::FileAttributes;
+pub use error::WinFileInfoError;
+pub use expand::{expand_path, Environment, KnownFolder};
+pub use icacls::{parse_icacls_listing, parse_icacls_save, IcaclsEntry};
+pub use key::WindowsPathKey;
+pub use kind::EntityKind;
+pub use long_path::{promote_long_path, strip_verbatim_prefix};
+pub use path::{relative_to, PathPrefix, WindowsPath};
+pub use runner::{CommandOutput, CommandRunner, FixtureRunner, PowerShellRunner};
+pub use sddl::{DescriptorControl, SecurityDescriptor};
+pub use session::Session;
+pub use sid::Sid;
+pub use short_name::{expand_short_path, expand_short_path_with, generate_short_name, is_short_name};
+pub use timestamp::WindowsTimestamp;
+pub use translate::{posix_to_windows, windows_to_posix, wsl_to_windows, PosixStyle};
+pub use validation::{sanitize_file_name, validate_file_name, validate_path, NameProblem};
+pub use wildcard::{is_name_in_expression, WildcardFilter};
+
+use json::JsonValue;
+
 #[derive(Debug, Clone)]
 pub struct WindowsEntity {
     pub mode: Vec<String>,
     pub types: Vec<String>,
-    pub owner: String,
-    pub last_write_time: String,
+    pub owner: String, // the account name, or the sid string when windows couldn't resolve it
+    pub owner_sid: Option<Sid>,
+    pub last_write_time: Option<WindowsTimestamp>,
     pub name: String,
-    pub creation_time: String,
-    pub attributes: String,
-    pub last_access_time: String,
-    pub size: i32,
-    pub absolute_path: String
+    pub creation_time: Option<WindowsTimestamp>,
+    pub attributes: FileAttributes,
+    pub last_access_time: Option<WindowsTimestamp>,
+    pub size: Option<u64>, // as bytes, "None" for directories and when powershell can't read it
+    pub absolute_path: String,
+    pub directory_size: Option<DirectorySize> // only filled for directories when "QueryOptions::directory_sizes" is on
+}
+
+// total size of all the files inside of a directory, recursively. "logical" is the sum of their lengths and
+// "allocated" is the space they use on the disk, rounded up to the cluster size of their volume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DirectorySize {
+    pub logical: u64,
+    pub allocated: u64
+}
+
+// options for the queries which run via "_with_options" functions.
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions {
+    // calculates "directory_size" of the directory entities. it walks the whole tree under every directory, so it's
+    // off by default.
+    pub directory_sizes: bool,
+    // translates the wsl, msys and cygwin paths like "/mnt/c/Users" or "/c/Users" to windows paths before the
+    // powershell query, for the tools that run under them. the native backend takes the paths as they are.
+    pub translate_posix_paths: bool,
+    // with "translate_posix_paths", the wsl paths which are not on a windows drive, like "/home/necoo", are
+    // translated to the "\\wsl.localhost" share of that distro.
+    pub wsl_distro: Option<String>,
+    // expands the "%VAR%", "$env:VAR" and "shell:Desktop" parts of the paths with this environment, before they
+    // are translated. the native backend expands them too.
+    pub environment: Option<Environment>
+}
+
+impl WindowsEntity {
+    pub fn kind(&self) -> EntityKind {
+        EntityKind::from_attributes(self.attributes)
+    }
+
+    // the account name of the owner, "None" when there is only its sid, like for the files of deleted users.
+    pub fn owner_account(&self) -> Option<&str> {
+        match self.owner.is_empty() || Sid::parse(&self.owner).is_ok() {
+            true => None,
+            false => Some(&self.owner)
+        }
+    }
+
+    // key of "absolute_path", for keeping the entities in a "HashSet" or "BTreeMap" without the same file twice.
+    pub fn path_key(&self) -> WindowsPathKey {
+        WindowsPathKey::new(&self.absolute_path)
+    }
 }
 
-#[cfg(target_os = "windows")]
 pub struct Permissions<'a> {
     pub entity_types: Vec<&'a str>,
     pub permissions: Vec<&'a str>
 }
 
+// properties we select for every entity. timestamps converted to round-trip format on powershell side, otherwise
+// "ConvertTo-Json" writes them as "\/Date(...)\/". attributes are sent as their dword, since .net has no names
+// for some of them.
+const ENTITY_PROPERTIES: &str = "Mode, @{Name='Owner'; Expression={$acl = Get-Acl -LiteralPath $_.FullName; if ($acl) { [ordered]@{Name = $acl.Owner; Sid = $acl.GetOwner([Security.Principal.SecurityIdentifier]).Value} }}}, @{Name='LastWriteTime'; Expression={$_.LastWriteTime.ToString('o')}}, Name, @{Name='CreationTime'; Expression={$_.CreationTime.ToString('o')}}, @{Name='Attributes'; Expression={[int]$_.Attributes}}, @{Name='LastAccessTime'; Expression={$_.LastAccessTime.ToString('o')}}, Length, FullName";
 
-#[cfg(target_os = "windows")]
-fn check_entity_permissions(perm_str: &str) -> Permissions {
-    let split_the_perm_str: Vec<&str> = perm_str.split("").collect();
+// finds the cluster size of the volume that given path is in and caches it, falls back to 4096 when it can't.
+const CLUSTER_SIZE_FUNCTION: &str = "$clusterSizes = @{}; function Get-ClusterSize($path) { $drive = [string](Split-Path -Qualifier $path -ErrorAction SilentlyContinue); if (-not $clusterSizes.ContainsKey($drive)) { $size = (Get-CimInstance Win32_Volume -Filter ('DriveLetter=''' + $drive + '''') -ErrorAction SilentlyContinue).BlockSize; $clusterSizes[$drive] = $(if ($size) { [long]$size } else { [long]4096 }) }; $clusterSizes[$drive] };";
+
+const DIRECTORY_SIZE_PROPERTY: &str = "@{Name='DirectorySize'; Expression={if ($_.PSIsContainer) { $cluster = Get-ClusterSize $_.FullName; $logical = [long]0; $allocated = [long]0; Get-ChildItem -LiteralPath $_.FullName -Recurse -File -Force -ErrorAction SilentlyContinue | ForEach-Object { $logical += $_.Length; $allocated += [long][Math]::Ceiling($_.Length / $cluster) * $cluster }; [ordered]@{Logical = $logical; Allocated = $allocated} }}}";
+
+// given path with its variables and known folders expanded, if "QueryOptions::environment" is set.
+fn expanded_path(path: &str, options: &QueryOptions) -> Result<String, WinFileInfoError> {
+    match &options.environment {
+        Some(environment) => expand::expand_path(path, environment),
+        None => Ok(path.to_string())
+    }
+}
+
+// path that the query runs for, given path is used as it is unless it has to be expanded or translated.
+fn input_path(path: &str, options: &QueryOptions) -> Result<String, WinFileInfoError> {
+    let path = expanded_path(path, options)?;
+
+    if !options.translate_posix_paths || !path.starts_with('/') {
+        return Ok(path);
+    }
+
+    match &options.wsl_distro {
+        Some(distro) => Ok(translate::wsl_to_windows(&path, distro)),
+        None => Ok(translate::posix_to_windows(&path).unwrap_or(path))
+    }
+}
 
+// builds the whole command, "source" is the command which gives the entities, like "Get-Item -LiteralPath 'C:\Users'".
+fn entity_query(source: &str, options: &QueryOptions) -> String {
+    match options.directory_sizes {
+        true => format!("{} {} | Select-Object {}, {} | ConvertTo-Json -Compress -Depth 2", CLUSTER_SIZE_FUNCTION, source, ENTITY_PROPERTIES, DIRECTORY_SIZE_PROPERTY),
+        false => format!("{} | Select-Object {} | ConvertTo-Json -Compress -Depth 2", source, ENTITY_PROPERTIES)
+    }
+}
+
+fn check_entity_permissions(perm_str: &str) -> Permissions<'static> {
     let mut entity_types = vec![];
     let mut all_permissions = vec![];
 
-    for permission_string in split_the_perm_str.into_iter() {
-        match permission_string {
-            "d" => {
+    for permission_char in perm_str.chars() {
+        match permission_char {
+            'd' => {
                 entity_types.push("directory");
                 all_permissions.push("directory");
             },
-            "a" => {
+            'a' => {
                 entity_types.push("archive");
                 all_permissions.push("archive");
             },
-            "r" => all_permissions.push("read-only"),
-            "h" => all_permissions.push("hidden"),
-            "s" => all_permissions.push("system"),
-            "l" => {
+            'r' => all_permissions.push("read-only"),
+            'h' => all_permissions.push("hidden"),
+            's' => all_permissions.push("system"),
+            'l' => {
                 entity_types.push("reparse-point-or-symlink");
                 all_permissions.push("reparse-point-or-symlink");
             },
-            &_ => ()
+            _ => ()
         }
     }
 
-    return Permissions {
+    Permissions {
         entity_types,
         permissions: all_permissions
     }
 }
 
-#[cfg(target_os = "windows")]
-fn extract_the_output<'a>(output: &'a Output) -> Vec<&'a str> {
-    let our_output = from_utf8(&output.stdout).unwrap();
-    let mut individual_lines: Vec<&'a str> = vec![];
-    let mut start_num: usize = 0;
-    let mut updated_start_num: usize = 0;
-
-    for (index, line) in our_output.lines().into_iter().enumerate() {
-        if index < 2 {
-            continue;
-        }
+fn invalid_entity(entity: &JsonValue, reason: String) -> WinFileInfoError {
+    WinFileInfoError::unparseable(entity.to_string(), reason)
+}
 
-        if index == updated_start_num {
-            start_num = start_num + 8 as usize;
-        }
+fn required_string_field(entity: &JsonValue, field: &str) -> Result<String, WinFileInfoError> {
+    match entity.get(field) {
+        Some(JsonValue::String(value)) => Ok(value.clone()),
+        Some(_) => Err(invalid_entity(entity, format!("'{}' field of the entity is not a string", field))),
+        None => Err(invalid_entity(entity, format!("entity has no '{}' field", field)))
+    }
+}
 
-        if index >= 2 {
-            if index == 2 {
-                start_num = 2;
-                updated_start_num = start_num + 8 as usize;
-            }
+// null or missing fields are empty strings, since powershell gives null for the things it can't read, like
+// owners of the entities that we have no access.
+fn optional_string_field(entity: &JsonValue, field: &str) -> Result<String, WinFileInfoError> {
+    match entity.get(field) {
+        Some(JsonValue::String(value)) => Ok(value.clone()),
+        Some(JsonValue::Null) | None => Ok("".to_string()),
+        Some(_) => Err(invalid_entity(entity, format!("'{}' field of the entity is not a string", field)))
+    }
+}
 
-            if index <= updated_start_num {
-                individual_lines.push(line);
-            }
+// the owner comes with its name and its sid. the older queries gave only the name, which is the sid string
+// itself when windows couldn't resolve it.
+fn owner_field(entity: &JsonValue, field: &str) -> Result<(String, Option<Sid>), WinFileInfoError> {
+    match entity.get(field) {
+        Some(owner @ JsonValue::Object(_)) => {
+            let sid = match optional_string_field(owner, "Sid")?.as_str() {
+                "" => None,
+                sid => Some(Sid::parse(sid).map_err(|_| invalid_entity(entity, format!("'{}' field of the entity has an invalid sid", field)))?)
+            };
+
+            Ok((optional_string_field(owner, "Name")?, sid))
+        },
+        _ => {
+            let owner = optional_string_field(entity, field)?;
+            let sid = Sid::parse(&owner).ok();
 
-            if index == updated_start_num {
-                updated_start_num = updated_start_num + 8 as usize;
-            }
+            Ok((owner, sid))
         }
     }
+}
 
-    return individual_lines;
+// timestamps come in round-trip format, but the culture specific formats are also accepted.
+fn optional_timestamp_field(entity: &JsonValue, field: &str) -> Result<Option<WindowsTimestamp>, WinFileInfoError> {
+    match optional_string_field(entity, field)?.as_str() {
+        "" => Ok(None),
+        timestamp => WindowsTimestamp::parse(timestamp).map(Some).map_err(|_| invalid_entity(entity, format!("'{}' field of the entity is not a timestamp", field)))
+    }
 }
 
-#[cfg(target_os = "windows")]
-fn empty_lines_cleanup(lines_of_output: Vec<&str>) -> Vec<&str> {
-    let empty_string = "".to_string();
+// attributes come as their dword, older "ReadOnly, Archive" form also accepted.
+fn attributes_field(entity: &JsonValue, field: &str) -> Result<FileAttributes, WinFileInfoError> {
+    match entity.get(field) {
+        Some(JsonValue::Number(attributes)) => match attributes.parse::<u32>() {
+            Ok(bits) => Ok(FileAttributes::from_bits_retain(bits)),
+            Err(_) => Err(invalid_entity(entity, format!("'{}' field of the entity is not a valid dword", field)))
+        },
+        Some(JsonValue::String(attributes)) => attributes.parse().map_err(|_| invalid_entity(entity, format!("'{}' field of the entity has unknown attributes", field))),
+        Some(JsonValue::Null) | None => Ok(FileAttributes::empty()),
+        Some(_) => Err(invalid_entity(entity, format!("'{}' field of the entity is not a number or string", field)))
+    }
+}
 
-    return lines_of_output.into_iter().filter(|line| *line != empty_string).collect();
+fn optional_size_field(entity: &JsonValue, field: &str) -> Result<Option<u64>, WinFileInfoError> {
+    match entity.get(field) {
+        Some(JsonValue::Number(size)) => match size.parse::<u64>() {
+            Ok(size) => Ok(Some(size)),
+            Err(_) => Err(invalid_entity(entity, format!("'{}' field of the entity is not a valid size", field)))
+        },
+        Some(JsonValue::Null) | None => Ok(None),
+        Some(_) => Err(invalid_entity(entity, format!("'{}' field of the entity is not a number", field)))
+    }
 }
 
-#[cfg(target_os = "windows")]
-fn format_the_output(lines_of_output: Vec<&str>) -> Vec<String> {
-    let mut entity_strings_collector = vec![];
-    let mut final_vector = vec![];
-    let mut concatenation_num: i8 = 0;
-    let seperator = " &*& ".to_string();
-    
-    for line in lines_of_output.into_iter() {
-        match line {
-            "" => (),
-            &_ => {
-                entity_strings_collector.push(line);
-                entity_strings_collector.push(seperator.as_str());
-                concatenation_num = concatenation_num + 1;
-            }
-        };
-
-        if &concatenation_num == &9 {
-            let entity_strings_collector = &entity_strings_collector;
-            let entity_strings_collector = entity_strings_collector.join("");
-            final_vector.push(entity_strings_collector);
-            let _entity_strings_collector = &String::new();
-            let _entity_strings_collector: Vec<String> = vec![];
-            concatenation_num = 0
-        }
+fn create_windows_entity(entity: &JsonValue) -> Result<WindowsEntity, WinFileInfoError> {
+    if !matches!(entity, JsonValue::Object(_)) {
+        return Err(invalid_entity(entity, "expected an entity object".to_string()));
     }
 
-    return final_vector.into_iter().map(|s| s.clone()).collect();
+    let mode = required_string_field(entity, "Mode")?;
+
+    // directories has no "Length", so it comes as null or doesn't come at all.
+    let size = optional_size_field(entity, "Length")?;
+
+    let directory_size = match entity.get("DirectorySize") {
+        Some(JsonValue::Null) | None => None,
+        Some(directory_size) => Some(DirectorySize {
+            logical: optional_size_field(directory_size, "Logical")?.unwrap_or(0),
+            allocated: optional_size_field(directory_size, "Allocated")?.unwrap_or(0)
+        })
+    };
+
+    let entity_permissions = check_entity_permissions(&mode);
+    let (owner, owner_sid) = owner_field(entity, "Owner")?;
+
+    Ok(WindowsEntity {
+        mode: entity_permissions.permissions.into_iter().map(|x| x.to_string()).collect(),
+        types: entity_permissions.entity_types.into_iter().map(|x| x.to_string()).collect(),
+        owner,
+        owner_sid,
+        last_write_time: optional_timestamp_field(entity, "LastWriteTime")?,
+        name: required_string_field(entity, "Name")?,
+        creation_time: optional_timestamp_field(entity, "CreationTime")?,
+        attributes: attributes_field(entity, "Attributes")?,
+        last_access_time: optional_timestamp_field(entity, "LastAccessTime")?,
+        size,
+        absolute_path: long_path::strip_verbatim_prefix(&required_string_field(entity, "FullName")?),
+        directory_size
+    })
 }
 
-#[cfg(target_os = "windows")]
-fn split_the_formatted_output(formatted_output: Vec<String>) -> Vec<WindowsEntity> {
-    let mut final_vector: Vec<WindowsEntity> = vec![];
-    
-    for line in formatted_output.into_iter() {
-        let split_the_line: Vec<&str> = line.split("&*&").collect();
+// parses the "ConvertTo-Json" output of a query. powershell writes nothing when there is no entity, a single
+// object when there is one and an array when there is more.
+fn parse_entities(stdout: &str) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    if stdout.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    match json::parse(stdout)? {
+        JsonValue::Array(entities) => entities.iter().map(create_windows_entity).collect(),
+        entity => Ok(vec![create_windows_entity(&entity)?])
+    }
+}
 
-        let windows_entity = create_windows_entity(split_the_line);
+// runs the command and returns its stdout. if powershell wrote nothing to stdout but something to stderr, the
+// command failed and stderr tells us why. when there is output on both of them, like listing a folder which
+// has some children that we can't read, we use what we got.
+fn run_query<R: CommandRunner>(runner: &R, path: &str, command: &str) -> Result<String, WinFileInfoError> {
+    let answer = runner.run(command).map_err(WinFileInfoError::ShellNotAvailable)?;
+    let stderr = String::from_utf8_lossy(&answer.stderr).to_string();
 
-        final_vector.push(windows_entity);
+    let stdout = match String::from_utf8(answer.stdout) {
+        Ok(stdout) => stdout,
+        Err(_) => return Err(WinFileInfoError::NonUtf8Output { stderr: Some(stderr).filter(|stderr| !stderr.trim().is_empty()) })
+    };
+
+    if stdout.trim().is_empty() && !stderr.trim().is_empty() {
+        return Err(WinFileInfoError::from_stderr(path, &stderr));
     }
 
-    return final_vector
+    Ok(stdout)
 }
 
 #[cfg(target_os = "windows")]
-fn create_windows_entity(get_splitted_line: Vec<&str>) -> WindowsEntity {
-    let mut mode = "".to_string();
-    let mut owner = "".to_string();
-    let mut last_write_time = "".to_string();
-    let mut name = "".to_string();
-    let mut creation_time = "".to_string();
-    let mut attributes = "".to_string();
-    let mut last_access_time = "".to_string();
-    let mut size: i32 = 0;
-    let mut full_name = "".to_string();
-
-    for splitted_line in get_splitted_line.into_iter() {
-        let first_seventeen_character: String = splitted_line.chars().take(17).collect();
-
-        match first_seventeen_character.as_str().trim() {
-            "Mode           :" => mode = splitted_line.split(" :").collect::<Vec<&str>>()[1].trim().to_string(),
-            "Owner          :" => owner = splitted_line.split(" :").collect::<Vec<&str>>()[1].trim().to_string(),
-            "LastWriteTime  :" => last_write_time = splitted_line.split(" :").collect::<Vec<&str>>()[1].trim().to_string(),
-            "Name           :" => name = splitted_line.split(" :").collect::<Vec<&str>>()[1].trim().to_string(),
-            "CreationTime   :" => creation_time = splitted_line.split(" :").collect::<Vec<&str>>()[1].trim().to_string(),
-            "Attributes     :" => attributes = splitted_line.split(" :").collect::<Vec<&str>>()[1].trim().to_string(),
-            "LastAccessTime :" => last_access_time = splitted_line.split(" :").collect::<Vec<&str>>()[1].trim().to_string(),
-            "Length         :" => size = splitted_line.split(" :").collect::<Vec<&str>>()[1].trim().parse().unwrap_or(0),
-            "FullName       :" => full_name = splitted_line.split(" :").collect::<Vec<&str>>()[1].trim().to_string(),
-            &_ => ()
-        }
-    }
+pub fn current_folder_info() -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    current_folder_info_with(&PowerShellRunner)
+}
 
-    let entity_permissions = check_entity_permissions(&mode);
+// there is no powershell on the other platforms, so the functions without a runner use the "native" backend there.
+#[cfg(not(target_os = "windows"))]
+pub fn current_folder_info() -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    native::current_folder_info()
+}
 
-    return WindowsEntity {
-        mode: entity_permissions.permissions.into_iter().map(|x| x.to_string()).collect(), 
-        types: entity_permissions.entity_types.into_iter().map(|x| x.to_string()).collect(), 
-        owner, last_write_time, name, creation_time, attributes, last_access_time, size, absolute_path: full_name
-    };
+// same with "current_folder_info()" but runs the commands via given runner, same goes for all the "_with" functions.
+pub fn current_folder_info_with<R: CommandRunner>(runner: &R) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    let current_path = current_dir().map_err(WinFileInfoError::Io)?;
+
+    match current_path.to_str() {
+        Some(current_path) => other_folder_info_with(runner, current_path),
+        None => Err(WinFileInfoError::Io(Error::new(ErrorKind::InvalidData, "current directory is not valid unicode")))
+    }
 }
 
 #[cfg(target_os = "windows")]
-pub fn current_folder_info() -> Vec<WindowsEntity> {
-    let current_path = current_dir().unwrap();
-    let current_path = current_path.as_path().to_str().unwrap();
-    let current_path = format!("{}", current_path);
-    let current_path = current_path.as_str();
+pub fn other_folder_info(path: &str) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    other_folder_info_with(&PowerShellRunner, path)
+}
 
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
+#[cfg(not(target_os = "windows"))]
+pub fn other_folder_info(path: &str) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    native::other_folder_info(path)
+}
 
-    let get_files_command = Command::new("powershell")
-                                                            .args(&["-Command", &format!("Get-ChildItem -Path '{}' | Select-Object Mode, @{{Name='Owner'; Expression={{(Get-Acl $_.FullName).Owner}}}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName", current_path)])
-                                                            .output();
+pub fn other_folder_info_with<R: CommandRunner>(runner: &R, path: &str) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    other_folder_info_with_options(runner, path, &QueryOptions::default())
+}
 
-    match get_files_command {
-        Ok(answer) => {
-            println!("get files command: {}", from_utf8(&answer.stderr).unwrap());
-            let extracted_output = extract_the_output(&answer);
+pub fn other_folder_info_with_options<R: CommandRunner>(runner: &R, path: &str, options: &QueryOptions) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    let path = &input_path(path, options)?;
+    let stdout = run_query(runner, path, &entity_query(&format!("Get-ChildItem {}", quote::literal_path(&long_path::promote_long_path(path))), options))?;
 
-            let output_cleanup = empty_lines_cleanup(extracted_output);
+    parse_entities(&stdout)
+}
 
-            let formatting_output = format_the_output(output_cleanup);
+#[cfg(target_os = "windows")]
+pub fn entity_info(path: &str) -> Result<WindowsEntity, WinFileInfoError> {
+    entity_info_with(&PowerShellRunner, path)
+}
 
-            return split_the_formatted_output(formatting_output);
-        },
-        Err(error) => {
-            println!("Some Error Happened when we try to get files: {}", error);
+#[cfg(not(target_os = "windows"))]
+pub fn entity_info(path: &str) -> Result<WindowsEntity, WinFileInfoError> {
+    native::entity_info(path)
+}
 
-            return vec![]
-        }
+pub fn entity_info_with<R: CommandRunner>(runner: &R, path: &str) -> Result<WindowsEntity, WinFileInfoError> {
+    entity_info_with_options(runner, path, &QueryOptions::default())
+}
+
+pub fn entity_info_with_options<R: CommandRunner>(runner: &R, path: &str, options: &QueryOptions) -> Result<WindowsEntity, WinFileInfoError> {
+    let path = &input_path(path, options)?;
+    let stdout = run_query(runner, path, &entity_query(&format!("Get-Item {}", quote::literal_path(&long_path::promote_long_path(path))), options))?;
+    let mut get_results = parse_entities(&stdout)?;
+
+    match get_results.len() {
+        0 => Err(WinFileInfoError::PathNotFound { path: path.to_string(), stderr: None }),
+        1 => Ok(get_results.remove(0)),
+        matches => Err(WinFileInfoError::AmbiguousMatch { path: path.to_string(), matches })
     }
 }
 
+// command lines of windows can be 32767 characters long, we leave some room for "powershell -Command".
+const MAX_COMMAND_LENGTH: usize = 30000;
+
+// asks every path with a single query and gives their results in the same order. a path that fails doesn't
+// fail the others. paths are split to more than one query when they don't fit to a single command line.
 #[cfg(target_os = "windows")]
-pub fn other_folder_info(path: &str) -> Result<Vec<WindowsEntity>, Error> {
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
+pub fn entities_info(paths: &[&str]) -> Vec<Result<WindowsEntity, WinFileInfoError>> {
+    entities_info_with(&PowerShellRunner, paths)
+}
 
-    let get_files_command = Command::new("powershell")
-                                                        .args(&["Get-ChildItem", "-Path", path, "|", "Select-Object", "Mode, @{Name='Owner'; Expression={(Get-Acl $_.FullName).Owner}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName"])
-                                                        .output();
+#[cfg(not(target_os = "windows"))]
+pub fn entities_info(paths: &[&str]) -> Vec<Result<WindowsEntity, WinFileInfoError>> {
+    native::entities_info(paths)
+}
 
-    return match get_files_command {
-        Ok(answer) => {
-            let extracted_output = extract_the_output(&answer);
+pub fn entities_info_with<R: CommandRunner>(runner: &R, paths: &[&str]) -> Vec<Result<WindowsEntity, WinFileInfoError>> {
+    entities_info_with_options(runner, paths, &QueryOptions::default())
+}
 
-            let output_cleanup = empty_lines_cleanup(extracted_output);
+pub fn entities_info_with_options<R: CommandRunner>(runner: &R, paths: &[&str], options: &QueryOptions) -> Vec<Result<WindowsEntity, WinFileInfoError>> {
+    let inputs: Vec<Result<String, WinFileInfoError>> = paths.iter().map(|path| input_path(path, options)).collect();
+    let paths: Vec<&str> = inputs.iter().filter_map(|input| input.as_deref().ok()).collect();
+    let free_space = MAX_COMMAND_LENGTH.saturating_sub(batch_query(&[], options).len());
+    let results: Vec<Result<WindowsEntity, WinFileInfoError>> = chunk_paths(&paths, free_space).into_iter().flat_map(|chunk| batch_entities_info(runner, chunk, options)).collect();
+    let mut results = results.into_iter();
+
+    // the paths which couldn't be expanded never reach the query, their errors go to their own places.
+    inputs.into_iter().map(|input| match input {
+        Ok(path) => results.next().unwrap_or(Err(WinFileInfoError::PathNotFound { path, stderr: None })),
+        Err(error) => Err(error)
+    }).collect()
+}
 
-            let formatting_output = format_the_output(output_cleanup);
+// every record of the batch has the index of its path and either the entity or the error that "Get-Item" threw.
+// "-InputObject" makes powershell write an array even if there is a single record.
+fn batch_query(paths: &[&str], options: &QueryOptions) -> String {
+    let paths: Vec<String> = paths.iter().map(|path| quote::single_quoted(&long_path::promote_long_path(path))).collect();
+    let select = match options.directory_sizes {
+        true => format!("{}, {}", ENTITY_PROPERTIES, DIRECTORY_SIZE_PROPERTY),
+        false => ENTITY_PROPERTIES.to_string()
+    };
+    let prelude = match options.directory_sizes {
+        true => CLUSTER_SIZE_FUNCTION,
+        false => ""
+    };
 
-            Ok(split_the_formatted_output(formatting_output))
-        },
-        Err(error) => {
-            println!("Some Error Happened when we try to get files: {}", error);
+    format!("{}$paths = @({}); $records = for ($i = 0; $i -lt $paths.Count; $i++) {{ try {{ $entity = Get-Item -LiteralPath $paths[$i] -ErrorAction Stop | Select-Object {}; [ordered]@{{Index = $i; Entity = $entity}} }} catch {{ [ordered]@{{Index = $i; Error = $_.FullyQualifiedErrorId; Message = $_.Exception.Message}} }} }}; ConvertTo-Json -InputObject @($records) -Compress -Depth 3", prelude, paths.join(","), select)
+}
+
+// splits the paths to the groups whose quoted length fits to "free_space". a path which is longer than that
+// alone still gets its own group.
+fn chunk_paths<'a, 'b>(paths: &'a [&'b str], free_space: usize) -> Vec<&'a [&'b str]> {
+    let mut chunks = vec![];
+    let (mut start, mut length) = (0, 0);
+
+    for (index, path) in paths.iter().enumerate() {
+        let quoted_length = quote::single_quoted(&long_path::promote_long_path(path)).len() + 1;
 
-            Err(error)
+        if index > start && length + quoted_length > free_space {
+            chunks.push(&paths[start..index]);
+            start = index;
+            length = 0;
         }
+
+        length += quoted_length;
+    }
+
+    if start < paths.len() {
+        chunks.push(&paths[start..]);
     }
+
+    chunks
 }
 
-#[cfg(target_os = "windows")]
-pub fn entity_info(path: &str) -> Result<WindowsEntity, Error> {
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
-
-    let get_files_command = Command::new("powershell")
-                                                        .args(&["Get-Item", "-Path", path, "|", "Select-Object", "Mode, @{Name='Owner'; Expression={(Get-Acl $_.FullName).Owner}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName"])
-                                                        .output();
-
-    match get_files_command {
-        Ok(answer) => {
-            let extracted_output = extract_the_output(&answer);
-
-            let output_cleanup = empty_lines_cleanup(extracted_output);
-
-            let formatting_output = format_the_output(output_cleanup);
-
-            let get_results = split_the_formatted_output(formatting_output);
-
-            return match get_results.len() as i8 {
-                0_i8 => {
-                    Ok(WindowsEntity {
-                        mode: vec![],
-                        types: vec![],
-                        owner: "".to_string(),
-                        last_write_time: "".to_string(),
-                        last_access_time: "".to_string(),
-                        name: "".to_string(),
-                        creation_time: "".to_string(),
-                        attributes: "".to_string(),
-                        size: 0,
-                        absolute_path: path.to_string()
-                    })
-                },
-                1_i8 => Ok(get_results[0].clone()),
-                _ => Err(Error::new(std::io::ErrorKind::Other, "Error"))
+fn batch_entities_info<R: CommandRunner>(runner: &R, paths: &[&str], options: &QueryOptions) -> Vec<Result<WindowsEntity, WinFileInfoError>> {
+    let records = match run_query(runner, &paths.join(", "), &batch_query(paths, options)).and_then(|stdout| json::parse(&stdout)) {
+        Ok(JsonValue::Array(records)) => records,
+        Ok(other) => return paths.iter().map(|_| Err(WinFileInfoError::unparseable(other.to_string(), "expected an array of batch records"))).collect(),
+        Err(error) => return paths.iter().map(|path| Err(error.for_path(path))).collect()
+    };
+
+    // the first record of every index, the others would be a bug of the query.
+    let mut by_index: HashMap<usize, &JsonValue> = HashMap::with_capacity(records.len());
+
+    for record in &records {
+        if let Some(JsonValue::Number(index)) = record.get("Index") {
+            if let Ok(index) = index.parse::<usize>() {
+                by_index.entry(index).or_insert(record);
             }
-        },
-        Err(error) => {
-            println!("Some Error Happened when we try to get entity: {}", error);
+        }
+    }
 
-            Err(Error::new(std::io::ErrorKind::Other, error))
+    paths.iter().enumerate().map(|(index, path)| {
+        match by_index.get(&index) {
+            Some(record) => batch_record(record, path),
+            None => Err(WinFileInfoError::unparseable(format!("[{}]", records.iter().map(|record| record.to_string()).collect::<Vec<String>>().join(",")), format!("there is no record for the path at index {}", index)))
         }
+    }).collect()
+}
+
+fn batch_record(record: &JsonValue, path: &str) -> Result<WindowsEntity, WinFileInfoError> {
+    match (record.get("Entity"), record.get("Error")) {
+        (Some(JsonValue::Array(entities)), _) => Err(WinFileInfoError::AmbiguousMatch { path: path.to_string(), matches: entities.len() }),
+        (Some(entity @ JsonValue::Object(_)), _) => create_windows_entity(entity),
+        (_, Some(JsonValue::String(error_id))) => {
+            let message = optional_string_field(record, "Message")?;
+
+            // same shape with the stderr of powershell, so the error is classified the same way.
+            Err(WinFileInfoError::from_stderr(path, &format!("{}\r\n    + FullyQualifiedErrorId : {}", message, error_id)))
+        },
+        _ => Err(WinFileInfoError::PathNotFound { path: path.to_string(), stderr: None })
     }
 }
 
-pub fn find_entity(name: &str, search_dest: &str) -> Result<WindowsEntity, std::io::Error> {
-    let format_the_command = format!("-Path '{}' -Filter '{}' -Recurse | Select-Object Mode, @{{Name='Owner'; Expression={{(Get-Acl $_.FullName).Owner}}}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName", search_dest, name);
-    let find_entity_command = std::process::Command::new("powershell.exe")
-                                                                    .arg("-Command")
-                                                                    .arg("Get-ChildItem")
-                                                                    .arg(format_the_command)
-                                                                    .output();
+#[cfg(target_os = "windows")]
+pub fn find_entity(name: &str, search_dest: &str) -> Result<WindowsEntity, WinFileInfoError> {
+    find_entity_with(&PowerShellRunner, name, search_dest)
+}
 
-    match find_entity_command {
-        Ok(file) => {
-            let parse_answer = String::from_utf8_lossy(&file.stdout);
+#[cfg(not(target_os = "windows"))]
+pub fn find_entity(name: &str, search_dest: &str) -> Result<WindowsEntity, WinFileInfoError> {
+    native::find_entity(name, search_dest)
+}
 
-            let lines = create_windows_entity(parse_answer.split("\n").collect::<Vec<&str>>());
+pub fn find_entity_with<R: CommandRunner>(runner: &R, name: &str, search_dest: &str) -> Result<WindowsEntity, WinFileInfoError> {
+    find_entity_with_options(runner, name, search_dest, &QueryOptions::default())
+}
 
-            Ok(lines)
-        },
-        Err(error) => {
-            Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, error))
-        }
+pub fn find_entity_with_options<R: CommandRunner>(runner: &R, name: &str, search_dest: &str, options: &QueryOptions) -> Result<WindowsEntity, WinFileInfoError> {
+    match find_entities_with_options(runner, name, search_dest, options)?.into_iter().next() {
+        Some(entity) => Ok(entity),
+        None => Err(WinFileInfoError::PathNotFound { path: format!("{}\\**\\{}", input_path(search_dest, options)?.trim_end_matches('\\'), name), stderr: None })
     }
 }
 
+#[cfg(target_os = "windows")]
+pub fn find_entities(name: &str, search_dest: &str) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    find_entities_with(&PowerShellRunner, name, search_dest)
+}
 
-pub fn find_entities(name: &str, search_dest: &str) -> Result<Vec<WindowsEntity>, std::io::Error> {
-    let format_the_command = format!("-Path '{}' -Filter '{}' -Recurse | Select-Object Mode, @{{Name='Owner'; Expression={{(Get-Acl $_.FullName).Owner}}}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName", search_dest, name);
-    let find_entity_command = std::process::Command::new("powershell.exe")
-                                                                    .arg("-Command")
-                                                                    .arg("Get-ChildItem")
-                                                                    .arg(format_the_command)
-                                                                    .output();
+#[cfg(not(target_os = "windows"))]
+pub fn find_entities(name: &str, search_dest: &str) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    native::find_entities(name, search_dest)
+}
 
-    match find_entity_command {
-        Ok(file) => {
-            let parse_answer = String::from_utf8_lossy(&file.stdout);
+pub fn find_entities_with<R: CommandRunner>(runner: &R, name: &str, search_dest: &str) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    find_entities_with_options(runner, name, search_dest, &QueryOptions::default())
+}
 
-            //let lines = create_windows_entity(parse_answer.split("\n").collect::<Vec<&str>>());
+// "search_dest" is translated like the paths of the other "_with_options" functions, "name" is a filter and
+// stays as it is.
+pub fn find_entities_with_options<R: CommandRunner>(runner: &R, name: &str, search_dest: &str, options: &QueryOptions) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    let search_dest = &input_path(search_dest, options)?;
+    let format_the_command = entity_query(&format!("Get-ChildItem {} -Filter {} -Recurse", quote::literal_path(&long_path::promote_long_path(search_dest)), quote::single_quoted(name)), options);
 
-            let lines = split_the_formatted_output(format_the_output(parse_answer.split("\n").collect::<Vec<&str>>()));
+    parse_entities(&run_query(runner, search_dest, &format_the_command)?)
+}
 
-            Ok(lines)
+#[cfg(target_os = "windows")]
+pub fn entity_kind(path: &str) -> Result<EntityKind, WinFileInfoError> {
+    entity_kind_with(&PowerShellRunner, path)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn entity_kind(path: &str) -> Result<EntityKind, WinFileInfoError> {
+    native::entity_kind(path)
+}
+
+// only asks the attributes of the entity, which is way cheaper than "entity_info()" if you only need to know
+// what the entity is.
+pub fn entity_kind_with<R: CommandRunner>(runner: &R, path: &str) -> Result<EntityKind, WinFileInfoError> {
+    entity_kind_with_options(runner, path, &QueryOptions::default())
+}
+
+// only the path options are used, there are no sizes to calculate here.
+pub fn entity_kind_with_options<R: CommandRunner>(runner: &R, path: &str, options: &QueryOptions) -> Result<EntityKind, WinFileInfoError> {
+    let path = &input_path(path, options)?;
+    let stdout = run_query(runner, path, &format!("Get-Item {} -Force | ForEach-Object {{ [int]$_.Attributes }} | ConvertTo-Json -Compress", quote::literal_path(&long_path::promote_long_path(path))))?;
+
+    if stdout.trim().is_empty() {
+        return Err(WinFileInfoError::PathNotFound { path: path.to_string(), stderr: None });
+    }
+
+    match json::parse(&stdout)? {
+        JsonValue::Number(bits) => match bits.parse::<u32>() {
+            Ok(bits) => Ok(EntityKind::from_attributes(FileAttributes::from_bits_retain(bits))),
+            Err(_) => Err(WinFileInfoError::unparseable(bits, "it's not a valid attribute dword"))
         },
-        Err(error) => {
-            Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, error))
-        }
+        JsonValue::Array(items) => Err(WinFileInfoError::AmbiguousMatch { path: path.to_string(), matches: items.len() }),
+        other => Err(WinFileInfoError::unparseable(other.to_string(), "expected the attributes of the entity"))
     }
 }
 
+// the deprecated "is_*" functions return false when we can't get the entity, they have no way to give the
+// error. "entity_kind()" gives it.
+fn entity_kind_or_default<R: CommandRunner>(runner: &R, path: &str) -> EntityKind {
+    entity_kind_with(runner, path).unwrap_or_default()
+}
+
+#[cfg(target_os = "windows")]
+#[deprecated(since = "0.5.0", note = "use `entity_kind(path)?.is_dir()` instead")]
+pub fn is_directory(path: &str) -> bool {
+    entity_kind_or_default(&PowerShellRunner, path).is_dir()
+}
+
+#[deprecated(since = "0.5.0", note = "use `entity_kind_with(runner, path)?.is_dir()` instead")]
+pub fn is_directory_with<R: CommandRunner>(runner: &R, path: &str) -> bool {
+    entity_kind_or_default(runner, path).is_dir()
+}
+
+#[cfg(target_os = "windows")]
+#[deprecated(since = "0.5.0", note = "use `entity_kind(path)?.is_archive()` instead")]
+pub fn is_archive(path: &str) -> bool {
+    entity_kind_or_default(&PowerShellRunner, path).is_archive()
+}
+
+#[deprecated(since = "0.5.0", note = "use `entity_kind_with(runner, path)?.is_archive()` instead")]
+pub fn is_archive_with<R: CommandRunner>(runner: &R, path: &str) -> bool {
+    entity_kind_or_default(runner, path).is_archive()
+}
+
+#[cfg(target_os = "windows")]
+#[deprecated(since = "0.5.0", note = "use `entity_kind(path)?.is_reparse()` instead")]
+pub fn is_reparse_point_or_symlink(path: &str) -> bool {
+    entity_kind_or_default(&PowerShellRunner, path).is_reparse()
+}
+
+#[deprecated(since = "0.5.0", note = "use `entity_kind_with(runner, path)?.is_reparse()` instead")]
+pub fn is_reparse_point_or_symlink_with<R: CommandRunner>(runner: &R, path: &str) -> bool {
+    entity_kind_or_default(runner, path).is_reparse()
+}
+
+#[cfg(target_os = "windows")]
+#[deprecated(since = "0.5.0", note = "use `entity_kind(path)?` and combine `is_dir()` with `is_archive()` instead")]
+pub fn is_directory_and_archive(path: &str) -> bool {
+    let kind = entity_kind_or_default(&PowerShellRunner, path);
+
+    kind.is_dir() && kind.is_archive()
+}
+
+#[deprecated(since = "0.5.0", note = "use `entity_kind_with(runner, path)?` and combine `is_dir()` with `is_archive()` instead")]
+pub fn is_directory_and_archive_with<R: CommandRunner>(runner: &R, path: &str) -> bool {
+    let kind = entity_kind_or_default(runner, path);
+
+    kind.is_dir() && kind.is_archive()
+}
+
+#[cfg(target_os = "windows")]
+#[deprecated(since = "0.5.0", note = "use `entity_kind(path)?` and combine `is_dir()` with `is_reparse()` instead")]
+pub fn is_directory_and_reparse_point_or_symlink(path: &str) -> bool {
+    let kind = entity_kind_or_default(&PowerShellRunner, path);
+
+    kind.is_dir() && kind.is_reparse()
+}
+
+#[deprecated(since = "0.5.0", note = "use `entity_kind_with(runner, path)?` and combine `is_dir()` with `is_reparse()` instead")]
+pub fn is_directory_and_reparse_point_or_symlink_with<R: CommandRunner>(runner: &R, path: &str) -> bool {
+    let kind = entity_kind_or_default(runner, path);
+
+    kind.is_dir() && kind.is_reparse()
+}
+
+#[cfg(target_os = "windows")]
+#[deprecated(since = "0.5.0", note = "use `entity_kind(path)?` and combine `is_archive()` with `is_reparse()` instead")]
+pub fn is_archive_and_reparse_point_or_symlink(path: &str) -> bool {
+    let kind = entity_kind_or_default(&PowerShellRunner, path);
+
+    kind.is_archive() && kind.is_reparse()
+}
+
+#[deprecated(since = "0.5.0", note = "use `entity_kind_with(runner, path)?` and combine `is_archive()` with `is_reparse()` instead")]
+pub fn is_archive_and_reparse_point_or_symlink_with<R: CommandRunner>(runner: &R, path: &str) -> bool {
+    let kind = entity_kind_or_default(runner, path);
+
+    kind.is_archive() && kind.is_reparse()
+}
+
+#[cfg(target_os = "windows")]
+#[deprecated(since = "0.5.0", note = "use `entity_kind(path)?` and combine `is_dir()`, `is_archive()` and `is_reparse()` instead")]
+pub fn is_directory_and_archive_and_reparse_point_or_symlink(path: &str) -> bool {
+    let kind = entity_kind_or_default(&PowerShellRunner, path);
+
+    kind.is_dir() && kind.is_archive() && kind.is_reparse()
+}
+
+#[deprecated(since = "0.5.0", note = "use `entity_kind_with(runner, path)?` and combine `is_dir()`, `is_archive()` and `is_reparse()` instead")]
+pub fn is_directory_and_archive_and_reparse_point_or_symlink_with<R: CommandRunner>(runner: &R, path: &str) -> bool {
+    let kind = entity_kind_or_default(runner, path);
+
+    kind.is_dir() && kind.is_archive() && kind.is_reparse()
+}
+
 // this functions is here for the situations which you need a windows path with double backslash or one backslash on every level and you
 // have only other.
 
@@ -355,6 +703,7 @@ pub fn find_entities(name: &str, search_dest: &str) -> Result<Vec<WindowsEntity>
 // let path = windows_paths_two_backslash("C:\\Users");
 
 #[cfg(target_os = "windows")]
+#[deprecated(since = "0.5.0", note = "it mangles unc paths, use `WindowsPath` instead")]
 pub fn windows_paths_two_backslash(path: &str) -> String {
     if path.contains("\\") {
         let split_the_path: Vec<&str> = path.split("\\").collect();
@@ -391,6 +740,7 @@ pub fn windows_paths_two_backslash(path: &str) -> String {
 // let path = windows_paths_one_backslash("C:\\\\Users");
 
 #[cfg(target_os = "windows")]
+#[deprecated(since = "0.5.0", note = "it mangles unc paths, use `WindowsPath` instead")]
 pub fn windows_paths_one_backslash(path: &str) -> String {
     if path.contains("\\\\") {
         let split_the_path: Vec<&str> = path.split("\\\\").collect();
@@ -421,356 +771,29 @@ pub fn windows_paths_one_backslash(path: &str) -> String {
 }
 
 #[cfg(target_os = "windows")]
-pub fn get_current_user() -> String {
+pub fn get_current_user() -> Result<String, WinFileInfoError> {
     let current_user_command = Command::new("cmd")
                                                         .arg("/C")
                                                         .arg("echo")
                                                         .arg("%username%")
-                                                        .output();
+                                                        .output()
+                                                        .map_err(WinFileInfoError::ShellNotAvailable)?;
 
-    match current_user_command {
-        Ok(user) => {
-            from_utf8(&user.stdout).unwrap().trim().to_string()
-        },
-        Err(error) => {
-            eprintln!("This error occured on get_current_user() function: {}", error);
-
-            "".to_string()
-        }
-    }
-}
-
-#[cfg(target_os = "windows")]
-pub fn is_directory(path: &str) -> bool {
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
-
-    let get_files_command = Command::new("powershell")
-                                                        .args(&["Get-Item", "-Path", path, "|", "Select-Object", "Mode, @{Name='Owner'; Expression={(Get-Acl $_.FullName).Owner}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName"])
-                                                        .output();
-
-    match get_files_command {
-        Ok(answer) => {
-            let decode_output = from_utf8(&answer.stdout).unwrap();
-            let mut result = false;
-
-            for line in decode_output.lines().into_iter() {
-                if line.starts_with("Mode") {
-                    let split_the_line: &str = line.split(" :").collect::<Vec<&str>>()[1];
-
-                    let check_the_permissions = check_entity_permissions(split_the_line.trim());
-
-                    if check_the_permissions.entity_types.contains(&"directory") {
-                        result = true
-                    } else {
-                        result = false
-                    }
-                } else {
-                    continue;
-                }
-            }
-
-            return result
-        },
-        Err(error) => {
-            println!("This error occured: {}", error);
-
-            return false
-        }
-    }
-}
-
-#[cfg(target_os = "windows")]
-pub fn is_archive(path: &str) -> bool {
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
-
-    let get_files_command = Command::new("powershell")
-                                                        .args(&["Get-Item", "-Path", path, "|", "Select-Object", "Mode, @{Name='Owner'; Expression={(Get-Acl $_.FullName).Owner}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName"])
-                                                        .output();
-
-    match get_files_command {
-        Ok(answer) => {
-            let decode_output = from_utf8(&answer.stdout).unwrap();
-            let mut result = false;
-
-            for line in decode_output.lines().into_iter() {
-                if line.starts_with("Mode") {
-                    let split_the_line: &str = line.split(" :").collect::<Vec<&str>>()[1];
-
-                    let check_the_permissions = check_entity_permissions(split_the_line.trim());
-
-                    if check_the_permissions.entity_types.contains(&"archive") {
-                        result = true
-                    } else {
-                        result = false
-                    }
-                } else {
-                    continue;
-                }
-            }
-
-            return result
-        },
-        Err(error) => {
-            println!("This error occured: {}", error);
-
-            return false
-        }
-    }
-}
-
-#[cfg(target_os = "windows")]
-pub fn is_reparse_point_or_symlink(path: &str) -> bool {
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
-
-    let get_files_command = Command::new("powershell")
-                                                        .args(&["Get-Item", "-Path", path, "|", "Select-Object", "Mode, @{Name='Owner'; Expression={(Get-Acl $_.FullName).Owner}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName"])
-                                                        .output();
-
-    match get_files_command {
-        Ok(answer) => {
-            let decode_output = from_utf8(&answer.stdout).unwrap();
-            let mut result = false;
-
-            for line in decode_output.lines().into_iter() {
-                if line.starts_with("Mode") {
-                    let split_the_line: &str = line.split(" :").collect::<Vec<&str>>()[1];
-
-                    let check_the_permissions = check_entity_permissions(split_the_line.trim());
-
-                    if check_the_permissions.entity_types.contains(&"reparse-point-or-symlink") {
-                        result = true
-                    } else {
-                        result = false
-                    }
-                } else {
-                    continue;
-                }
-            }
-
-            return result
-        },
-        Err(error) => {
-            println!("This error occured: {}", error);
-
-            return false
-        }
-    }
-}
-
-#[cfg(target_os = "windows")]
-pub fn is_directory_and_archive(path: &str) -> bool {
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
-
-    let get_files_command = Command::new("powershell")
-                                                        .args(&["Get-Item", "-Path", path, "|", "Select-Object", "Mode, @{Name='Owner'; Expression={(Get-Acl $_.FullName).Owner}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName"])
-                                                        .output();
-
-    match get_files_command {
-        Ok(answer) => {
-            let decode_output = from_utf8(&answer.stdout).unwrap();
-            let mut result_num: i8 = 0;
-
-            for line in decode_output.lines().into_iter() {
-                if line.starts_with("Mode") {
-                    let split_the_line: &str = line.split(" :").collect::<Vec<&str>>()[1];
-
-                    let check_the_permissions = check_entity_permissions(split_the_line.trim());
-
-                    if check_the_permissions.entity_types.contains(&"archive") {
-                        result_num = result_num + 1;
-                    }
-
-                    if check_the_permissions.entity_types.contains(&"directory") {
-                        result_num = result_num + 1;
-                    }
-                } else {
-                    continue;
-                }
-            }
-
-            return match result_num {
-                1 => false,
-                2 => true,
-                _ => false
-            }
-        },
-        Err(error) => {
-            println!("This error occured: {}", error);
-
-            return false
-        }
-    }
-}
-
-#[cfg(target_os = "windows")]
-pub fn is_directory_and_reparse_point_or_symlink(path: &str) -> bool {
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
-
-    let get_files_command = Command::new("powershell")
-                                                        .args(&["Get-Item", "-Path", path, "|", "Select-Object", "Mode, @{Name='Owner'; Expression={(Get-Acl $_.FullName).Owner}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName"])
-                                                        .output();
-
-    match get_files_command {
-        Ok(answer) => {
-            let decode_output = from_utf8(&answer.stdout).unwrap();
-            let mut result_num: i8 = 0;
-
-            for line in decode_output.lines().into_iter() {
-                if line.starts_with("Mode") {
-                    let split_the_line: &str = line.split(" :").collect::<Vec<&str>>()[1];
-
-                    let check_the_permissions = check_entity_permissions(split_the_line.trim());
-
-                    if check_the_permissions.entity_types.contains(&"directory") {
-                        result_num = result_num + 1;
-                    }
-
-                    if check_the_permissions.entity_types.contains(&"reparse-point-or-symlink") {
-                        result_num = result_num + 1;
-                    }
-                } else {
-                    continue;
-                }
-            }
-
-            return match result_num {
-                1 => false,
-                2 => true,
-                _ => false
-            }
-        },
-        Err(error) => {
-            println!("This error occured: {}", error);
-
-            return false
-        }
-    }
-}
-
-#[cfg(target_os = "windows")]
-pub fn is_archive_and_reparse_point_or_symlink(path: &str) -> bool {
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
-
-    let get_files_command = Command::new("powershell")
-                                                        .args(&["Get-Item", "-Path", path, "|", "Select-Object", "Mode, @{Name='Owner'; Expression={(Get-Acl $_.FullName).Owner}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName"])
-                                                        .output();
-
-    match get_files_command {
-        Ok(answer) => {
-            let decode_output = from_utf8(&answer.stdout).unwrap();
-            let mut result_num: i8 = 0;
-
-            for line in decode_output.lines().into_iter() {
-                if line.starts_with("Mode") {
-                    let split_the_line: &str = line.split(" :").collect::<Vec<&str>>()[1];
-
-                    let check_the_permissions = check_entity_permissions(split_the_line.trim());
-
-                    if check_the_permissions.entity_types.contains(&"archive") {
-                        result_num = result_num + 1;
-                    }
-
-                    if check_the_permissions.entity_types.contains(&"reparse-point-or-symlink") {
-                        result_num = result_num + 1;
-                    }
-                } else {
-                    continue;
-                }
-            }
-
-            return match result_num {
-                1 => false,
-                2 => true,
-                _ => false
-            }
-        },
-        Err(error) => {
-            println!("This error occured: {}", error);
-
-            return false
-        }
-    }
-}
-
-#[cfg(target_os = "windows")]
-pub fn is_directory_and_archive_and_reparse_point_or_symlink(path: &str) -> bool {
-    let _turn_terminal_into_utf8 = Command::new("cmd")
-                                                    .args(&["/C", "chcp 65001"])
-                                                    .output()
-                                                    .expect("cannot convert terminal's character encoding to utf8");
-
-    let get_files_command = Command::new("powershell")
-                                                        .args(&["Get-Item", "-Path", path, "|", "Select-Object", "Mode, @{Name='Owner'; Expression={(Get-Acl $_.FullName).Owner}}, LastWriteTime, Name, CreationTime, Attributes, LastAccessTime, Length, FullName"])
-                                                        .output();
-
-    match get_files_command {
-        Ok(answer) => {
-            let decode_output = from_utf8(&answer.stdout).unwrap();
-            let mut result_num: i8 = 0;
-
-            for line in decode_output.lines().into_iter() {
-                if line.starts_with("Mode") {
-                    let split_the_line: &str = line.split(" :").collect::<Vec<&str>>()[1];
-
-                    let check_the_permissions = check_entity_permissions(split_the_line.trim());
-
-                    if check_the_permissions.entity_types.contains(&"archive") {
-                        result_num = result_num + 1;
-                    }
-
-                    if check_the_permissions.entity_types.contains(&"directory") {
-                        result_num = result_num + 1;
-                    }
-
-                    if check_the_permissions.entity_types.contains(&"reparse-point-or-symlink") {
-                        result_num = result_num + 1;
-                    }
-                } else {
-                    continue;
-                }
-            }
-
-            return match result_num {
-                1 => false,
-                2 => false,
-                3 => true,
-                _ => false
-            }
-        },
-        Err(error) => {
-            println!("This error occured: {}", error);
-
-            return false
-        }
+    match String::from_utf8(current_user_command.stdout) {
+        Ok(user) => Ok(user.trim().to_string()),
+        Err(_) => Err(WinFileInfoError::NonUtf8Output { stderr: None })
     }
 }
 
 #[cfg(target_os = "windows")]
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_get_current_user(){
-        println!("your current user: {}", get_current_user())
+        println!("your current user: {}", get_current_user().unwrap())
     }
 
     #[test]
@@ -783,7 +806,7 @@ mod tests {
 
     #[test]
     fn test_other_folder_info(){
-        let current_user = get_current_user();
+        let current_user = get_current_user().unwrap();
 
         let our_path = format!("C:\\Users\\{}\\Desktop", current_user);
 
@@ -796,7 +819,7 @@ mod tests {
 
     #[test]
     fn test_entity_info() {
-        let current_user = get_current_user();
+        let current_user = get_current_user().unwrap();
 
         let our_path = format!("C:\\Users\\{}\\Desktop", current_user);
 
@@ -808,9 +831,19 @@ mod tests {
         println!("entity_info() function running time as milliseconds: {}", function_ended.as_millis())
     }
 
+    #[test]
+    fn test_entity_kind(){
+        let current_user = get_current_user().unwrap();
+        let format_the_path = format!("C:\\Users\\{}\\OneDrive", current_user);
+
+        let kind = entity_kind(&format_the_path).unwrap();
+
+        assert!(kind.is_dir() && kind.is_reparse())
+    }
+
     #[test]
     fn test_is_directory(){
-        let current_user = get_current_user();
+        let current_user = get_current_user().unwrap();
 
         let our_path = format!("C:\\Users\\{}\\Desktop", current_user);
 
@@ -819,7 +852,7 @@ mod tests {
 
     #[test]
     fn test_is_archive(){
-        let current_user = get_current_user();
+        let current_user = get_current_user().unwrap();
 
         let our_path = format!("C:\\Users\\{}\\.cargo\\bin\\cargo.exe", current_user);
 
@@ -828,7 +861,7 @@ mod tests {
 
     #[test]
     fn test_is_symlink_or_reparse_point(){
-        let current_user = get_current_user();
+        let current_user = get_current_user().unwrap();
         let format_the_path = format!("C:\\Users\\{}\\OneDrive", current_user);
 
         assert_eq!(true, is_reparse_point_or_symlink(&format_the_path))
@@ -836,7 +869,7 @@ mod tests {
 
     #[test]
     fn test_is_directory_and_archive(){
-        let current_user = get_current_user();
+        let current_user = get_current_user().unwrap();
         let format_the_path = format!("C:\\Users\\{}\\OneDrive", current_user);
 
         assert_eq!(true, is_directory_and_archive(&format_the_path))
@@ -844,7 +877,7 @@ mod tests {
 
     #[test]
     fn test_is_directory_and_reparse_point_or_symlink(){
-        let current_user = get_current_user();
+        let current_user = get_current_user().unwrap();
         let format_the_path = format!("C:\\Users\\{}\\OneDrive", current_user);
 
         assert_eq!(true, is_directory_and_reparse_point_or_symlink(&format_the_path))
@@ -852,7 +885,7 @@ mod tests {
 
     #[test]
     fn test_is_archive_and_reparse_point_or_symlink(){
-        let current_user = get_current_user();
+        let current_user = get_current_user().unwrap();
         let format_the_path = format!("C:\\Users\\{}\\OneDrive", current_user);
 
         assert_eq!(true, is_archive_and_reparse_point_or_symlink(&format_the_path))
@@ -860,7 +893,7 @@ mod tests {
 
     #[test]
     fn test_is_directory_and_archive_and_reparse_point_or_symlink(){
-        let current_user = get_current_user();
+        let current_user = get_current_user().unwrap();
         let format_the_path = format!("C:\\Users\\{}\\OneDrive", current_user);
 
         assert_eq!(true, is_directory_and_archive_and_reparse_point_or_symlink(&format_the_path))
@@ -875,4 +908,424 @@ mod tests {
     fn test_find_entities(){
         assert_eq!(true, find_entities("Cargo.toml", "C:\\").is_ok())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod fixture_tests {
+    use super::*;
+
+    const FOLDER_LISTING: &str = include_str!("../tests/fixtures/folder_listing.json");
+    const ENTITY_ONEDRIVE: &str = include_str!("../tests/fixtures/entity_onedrive.json");
+    const COLON_AND_UNICODE_NAME: &str = include_str!("../tests/fixtures/colon_and_unicode_name.json");
+    const LONG_PATH: &str = include_str!("../tests/fixtures/long_path.json");
+    const FOLDER_LISTING_WITH_SIZES: &str = include_str!("../tests/fixtures/folder_listing_with_sizes.json");
+    const ENTITIES_BATCH: &str = include_str!("../tests/fixtures/entities_batch.json");
+    const OWNER_SIDS: &str = include_str!("../tests/fixtures/owner_sids.json");
+
+    #[test]
+    fn test_other_folder_info_with_fixture(){
+        let runner = FixtureRunner::new().with_stdout(FOLDER_LISTING);
+
+        let entities = other_folder_info_with(&runner, "C:\\Users\\necoo\\Desktop").unwrap();
+
+        assert_eq!(entities.len(), 4);
+        assert_eq!(entities[0].name, "projects");
+        assert_eq!(entities[0].types, vec!["directory"]);
+        assert_eq!(entities[0].size, None);
+        assert_eq!(entities[1].mode, vec!["directory", "hidden", "system", "reparse-point-or-symlink"]);
+        assert_eq!(entities[2].size, Some(2048));
+        assert_eq!(entities[2].owner, "DESKTOP-7KQ2\\necoo");
+        assert_eq!(entities[2].last_write_time.unwrap().to_string(), "2026-10-09T17:30:00.4821733+03:00");
+        assert_eq!(entities[2].last_write_time.unwrap().offset_minutes(), Some(180));
+        assert_eq!(entities[3].name, "çalışma planı.xlsx");
+        assert_eq!(entities[3].absolute_path, "C:\\Users\\necoo\\Desktop\\çalışma planı.xlsx");
+        assert!(runner.commands()[0].starts_with("Get-ChildItem -LiteralPath 'C:\\Users\\necoo\\Desktop' |"));
+        assert!(runner.commands()[0].ends_with("ConvertTo-Json -Compress -Depth 2"));
+    }
+
+    #[test]
+    fn test_other_folder_info_with_empty_folder(){
+        let runner = FixtureRunner::new().with_stdout("\r\n");
+
+        assert_eq!(other_folder_info_with(&runner, "C:\\empty").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_entity_info_with_fixture(){
+        let runner = FixtureRunner::new().with_stdout(ENTITY_ONEDRIVE);
+
+        let entity = entity_info_with(&runner, "C:\\Users\\necoo\\Desktop\\OneDrive").unwrap();
+
+        assert_eq!(entity.name, "OneDrive");
+        assert_eq!(entity.attributes, FileAttributes::HIDDEN | FileAttributes::SYSTEM | FileAttributes::DIRECTORY | FileAttributes::REPARSE_POINT);
+        assert_eq!(entity.attributes.to_string(), "Hidden, System, Directory, ReparsePoint");
+        assert_eq!(entity.absolute_path, "C:\\Users\\necoo\\Desktop\\OneDrive");
+    }
+
+    #[test]
+    fn test_owner_sids(){
+        let runner = FixtureRunner::new().with_stdout(OWNER_SIDS);
+        let entities = other_folder_info_with(&runner, "D:\\archive").unwrap();
+
+        assert!(runner.commands()[0].contains("GetOwner([Security.Principal.SecurityIdentifier])"));
+        assert_eq!(entities[0].owner, "BUILTIN\\Administrators");
+        assert_eq!(entities[0].owner_account(), Some("BUILTIN\\Administrators"));
+        assert_eq!(entities[0].owner_sid.as_ref().unwrap().well_known_name(), Some("BUILTIN\\Administrators"));
+
+        // the owner of that one was deleted, so there is only its sid:
+        assert_eq!(entities[1].owner, "S-1-5-21-3623811015-3361044348-30300820-1013");
+        assert_eq!(entities[1].owner_account(), None);
+        assert_eq!(entities[1].owner_sid.as_ref().unwrap().rid(), Some(1013));
+
+        assert_eq!(entities[2].owner, "");
+        assert_eq!(entities[2].owner_sid, None);
+
+        // the older queries gave only the name:
+        let entity = entity_info_with(&FixtureRunner::new().with_stdout(COLON_AND_UNICODE_NAME), "C:\\x").unwrap();
+
+        assert_eq!(entity.owner_account(), Some("DESKTOP-7KQ2\\necoo"));
+        assert_eq!(entity.owner_sid, None);
+    }
+
+    #[test]
+    fn test_entity_info_with_colon_and_unicode_name(){
+        let runner = FixtureRunner::new().with_stdout(COLON_AND_UNICODE_NAME);
+
+        let entity = entity_info_with(&runner, "C:\\Users\\necoo\\Desktop\\report : final 日本語 🙂.txt").unwrap();
+
+        assert_eq!(entity.name, "report : final 日本語 🙂.txt");
+        assert_eq!(entity.absolute_path, "C:\\Users\\necoo\\Desktop\\report : final 日本語 🙂.txt");
+        assert_eq!(entity.owner, "DESKTOP-7KQ2\\necoo");
+        // that fixture has the culture specific timestamps of the older versions:
+        assert_eq!(entity.last_write_time.unwrap().to_string(), "2026-10-18T14:02:11.0000000");
+    }
+
+    #[test]
+    fn test_entities_sorted_by_timestamps(){
+        let runner = FixtureRunner::new().with_stdout(FOLDER_LISTING);
+
+        let mut entities = other_folder_info_with(&runner, "C:\\Users\\necoo\\Desktop").unwrap();
+        entities.sort_by_key(|entity| entity.last_write_time);
+
+        let names: Vec<&str> = entities.iter().map(|entity| entity.name.as_str()).collect();
+
+        assert_eq!(names, vec!["OneDrive", "çalışma planı.xlsx", "notes.txt", "projects"]);
+    }
+
+    #[test]
+    fn test_entity_info_with_long_path(){
+        let runner = FixtureRunner::new().with_stdout(LONG_PATH);
+
+        let entity = entity_info_with(&runner, "C:\\build").unwrap();
+
+        assert_eq!(entity.absolute_path.chars().count(), 300);
+        assert!(entity.absolute_path.ends_with(&entity.name));
+        assert_eq!(entity.size, Some(5));
+    }
+
+    #[test]
+    fn test_long_paths_are_promoted(){
+        let deep = format!("C:\\build{}", "\\node_modules\\pkg".repeat(24));
+        let stdout = format!("{{\"Mode\":\"-a----\",\"Name\":\"index.js\",\"FullName\":\"\\\\\\\\?\\\\{}\\\\index.js\",\"Length\":5}}", deep.replace('\\', "\\\\"));
+        let runner = FixtureRunner::new().with_stdout(stdout.clone()).with_stdout(stdout).with_stdout("[]");
+
+        assert_eq!(deep.len(), 416);
+
+        let entities = other_folder_info_with(&runner, &deep).unwrap();
+        let entity = entity_info_with(&runner, &format!("{}\\index.js", deep)).unwrap();
+        let _ = entities_info_with(&runner, &[&deep, "C:\\short"]);
+        let commands = runner.commands();
+
+        assert_eq!(entities[0].absolute_path, format!("{}\\index.js", deep));
+        assert_eq!(entity.absolute_path, format!("{}\\index.js", deep));
+        assert!(commands[0].starts_with(&format!("Get-ChildItem -LiteralPath '\\\\?\\{}' |", deep)));
+        assert!(commands[1].starts_with(&format!("Get-Item -LiteralPath '\\\\?\\{}\\index.js' |", deep)));
+        assert!(commands[2].contains(&format!("$paths = @('\\\\?\\{}','C:\\short');", deep)));
+    }
+
+    #[test]
+    fn test_other_folder_info_with_directory_sizes(){
+        let runner = FixtureRunner::new().with_stdout(FOLDER_LISTING_WITH_SIZES);
+        let options = QueryOptions { directory_sizes: true, ..QueryOptions::default() };
+
+        let entities = other_folder_info_with_options(&runner, "D:\\media", &options).unwrap();
+
+        assert_eq!(entities[0].size, None);
+        assert_eq!(entities[0].directory_size, Some(DirectorySize { logical: 21_474_836_481, allocated: 21_474_840_576 }));
+        assert_eq!(entities[1].directory_size, Some(DirectorySize { logical: 0, allocated: 0 }));
+        assert_eq!(entities[2].size, Some(5_368_709_120));
+        assert_eq!(entities[2].directory_size, None);
+        assert_eq!(entities[2].attributes, FileAttributes::ARCHIVE | FileAttributes::SPARSE_FILE | FileAttributes::PINNED);
+        assert!(runner.commands()[0].contains("DirectorySize"));
+    }
+
+    #[test]
+    fn test_directory_sizes_are_opt_in(){
+        let runner = FixtureRunner::new().with_stdout(ENTITY_ONEDRIVE);
+
+        let entity = entity_info_with(&runner, "C:\\Users\\necoo\\Desktop\\OneDrive").unwrap();
+
+        assert_eq!(entity.directory_size, None);
+        assert!(!runner.commands()[0].contains("DirectorySize"));
+    }
+
+    #[test]
+    fn test_invalid_sizes_are_rejected(){
+        let runner = FixtureRunner::new().with_stdout("{\"Mode\":\"-a----\",\"Name\":\"a\",\"FullName\":\"C:\\\\a\",\"Length\":-1}")
+                                         .with_stdout("{\"Mode\":\"-a----\",\"Name\":\"a\",\"FullName\":\"C:\\\\a\",\"Length\":1.5}");
+
+        assert!(matches!(entity_info_with(&runner, "C:\\a"), Err(WinFileInfoError::UnparseableRecord { .. })));
+        assert!(matches!(entity_info_with(&runner, "C:\\a"), Err(WinFileInfoError::UnparseableRecord { .. })));
+    }
+
+    #[test]
+    fn test_entity_info_with_more_than_one_result(){
+        let runner = FixtureRunner::new().with_stdout(FOLDER_LISTING);
+
+        assert!(matches!(entity_info_with(&runner, "C:\\Users\\necoo\\Desktop\\*"), Err(WinFileInfoError::AmbiguousMatch { matches: 4, .. })));
+    }
+
+    #[test]
+    fn test_strict_parser_rejects_malformed_output(){
+        // format-list output of the older versions, truncated json and entities without required fields:
+        let runner = FixtureRunner::new().with_stdout("\r\n\r\nMode           : d-----\r\nName           : projects\r\n")
+                                         .with_stdout("[{\"Mode\":\"d-----\",\"Name\":\"projects\"")
+                                         .with_stdout("{\"Mode\":\"d-----\",\"Name\":\"projects\"}")
+                                         .with_stdout("{\"Mode\":\"-a----\",\"Name\":\"a.txt\",\"FullName\":\"C:\\\\a.txt\",\"Length\":\"12\"}")
+                                         .with_stdout("[1, 2]")
+                                         .with_stdout("{\"Mode\":\"d-----\",\"Name\":\"a\",\"FullName\":\"C:\\\\a\",\"LastWriteTime\":\"yesterday\"}");
+
+        for _ in 0..6 {
+            assert!(matches!(other_folder_info_with(&runner, "C:\\"), Err(WinFileInfoError::UnparseableRecord { .. })));
+        }
+    }
+
+    #[test]
+    fn test_entities_info_with_fixture(){
+        let runner = FixtureRunner::new().with_stdout(ENTITIES_BATCH);
+        let paths = ["C:\\Users\\necoo\\Desktop\\projects", "C:\\Users\\necoo\\Desktop\\missing.txt", "C:\\Windows\\CSC", "C:\\temp\\*.txt", "C:\\Users\\necoo\\Desktop\\notes.txt"];
+
+        let results = entities_info_with(&runner, &paths);
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].as_ref().unwrap().name, "projects");
+        assert!(matches!(&results[1], Err(WinFileInfoError::PathNotFound { path, stderr: Some(_) }) if path == paths[1]));
+        assert!(matches!(&results[2], Err(WinFileInfoError::AccessDenied { .. })));
+        assert!(matches!(&results[3], Err(WinFileInfoError::AmbiguousMatch { matches: 2, .. })));
+        assert_eq!(results[4].as_ref().unwrap().size, Some(2048));
+        assert_eq!(runner.commands().len(), 1);
+        assert!(runner.commands()[0].contains("$paths = @('C:\\Users\\necoo\\Desktop\\projects','C:\\Users"));
+    }
+
+    #[test]
+    fn test_entities_info_when_the_whole_batch_fails(){
+        let runner = FixtureRunner::new().with_stdout("[{\"Index\":0,\"Entity\":null}]").with_stdout("not json");
+
+        let results = entities_info_with(&runner, &["C:\\a", "C:\\b"]);
+
+        assert!(matches!(&results[0], Err(WinFileInfoError::PathNotFound { .. })));
+        assert!(matches!(&results[1], Err(WinFileInfoError::UnparseableRecord { .. })));
+
+        let results = entities_info_with(&runner, &["C:\\a", "C:\\b"]);
+
+        assert!(results.iter().all(|result| matches!(result, Err(WinFileInfoError::UnparseableRecord { .. }))));
+        assert!(entities_info_with(&runner, &[]).is_empty());
+        // and there is nothing left to run:
+        assert!(matches!(&entities_info_with(&runner, &["C:\\a", "C:\\b"])[1], Err(WinFileInfoError::ShellNotAvailable(_))));
+    }
+
+    #[test]
+    fn test_chunk_paths(){
+        let long_path = "C:\\".to_string() + &"x".repeat(40);
+        let paths = ["C:\\a", "C:\\bb", &long_path, "C:\\c"];
+
+        let chunks = chunk_paths(&paths, 20);
+
+        assert_eq!(chunks, vec![&paths[0..2], &paths[2..3], &paths[3..4]]);
+        assert_eq!(chunk_paths(&paths, MAX_COMMAND_LENGTH).len(), 1);
+
+        let many_paths: Vec<String> = (0..2000).map(|index| format!("C:\\Users\\necoo\\Documents\\file-{}.txt", index)).collect();
+        let many_paths: Vec<&str> = many_paths.iter().map(|path| path.as_str()).collect();
+        let chunks = chunk_paths(&many_paths, MAX_COMMAND_LENGTH - batch_query(&[], &QueryOptions::default()).len());
+
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.iter().map(|chunk| chunk.len()).sum::<usize>(), 2000);
+        assert!(chunks.iter().all(|chunk| batch_query(chunk, &QueryOptions::default()).len() <= MAX_COMMAND_LENGTH));
+    }
+
+    #[test]
+    fn test_find_entities_with_fixture(){
+        let runner = FixtureRunner::new().with_stdout(FOLDER_LISTING).with_stdout("");
+
+        assert_eq!(find_entities_with(&runner, "*", "C:\\Users").unwrap().len(), 4);
+        assert!(matches!(find_entity_with(&runner, "missing.txt", "C:\\Users"), Err(WinFileInfoError::PathNotFound { .. })));
+    }
+
+    #[test]
+    fn test_entity_kind_with_fixture(){
+        let runner = FixtureRunner::new().with_stdout("1072\r\n").with_stdout("").with_stdout("[32,16]");
+
+        let kind = entity_kind_with(&runner, "C:\\Users\\necoo\\Desktop\\OneDrive").unwrap();
+
+        assert!(kind.is_dir() && kind.is_reparse() && kind.is_archive());
+        assert!(!kind.is_hidden());
+        assert!(matches!(entity_kind_with(&runner, "C:\\missing"), Err(WinFileInfoError::PathNotFound { .. })));
+        assert!(matches!(entity_kind_with(&runner, "C:\\*"), Err(WinFileInfoError::AmbiguousMatch { matches: 2, .. })));
+        assert_eq!(runner.commands().len(), 3);
+    }
+
+    #[test]
+    fn test_errors_from_stderr(){
+        let runner = FixtureRunner::new().with_stderr("Get-Item : Cannot find path 'C:\\missing' because it does not exist.\r\n    + FullyQualifiedErrorId : PathNotFound,Microsoft.PowerShell.Commands.GetItemCommand")
+                                         .with_stderr("Get-ChildItem : Access to the path 'C:\\secret' is denied.\r\n    + FullyQualifiedErrorId : DirUnauthorizedAccessError,Microsoft.PowerShell.Commands.GetChildItemCommand")
+                                         .with_stderr("The term 'Get-Item' is not recognized");
+
+        match entity_info_with(&runner, "C:\\missing") {
+            Err(WinFileInfoError::PathNotFound { path, stderr }) => {
+                assert_eq!(path, "C:\\missing");
+                assert!(stderr.unwrap().contains("PathNotFound"));
+            },
+            other => panic!("expected PathNotFound, got {:?}", other)
+        }
+
+        assert!(matches!(other_folder_info_with(&runner, "C:\\secret"), Err(WinFileInfoError::AccessDenied { .. })));
+        assert!(matches!(entity_kind_with(&runner, "C:\\"), Err(WinFileInfoError::CommandFailed { .. })));
+        // the runner has nothing left to run the command with:
+        assert!(matches!(entity_info_with(&runner, "C:\\"), Err(WinFileInfoError::ShellNotAvailable(_))));
+    }
+
+    #[test]
+    fn test_non_utf8_output(){
+        let runner = FixtureRunner::new().with_output(CommandOutput::new(vec![0x7B, 0xFF, 0xFE, 0x7D], b"chcp failed".to_vec(), true));
+
+        match entity_info_with(&runner, "C:\\a") {
+            Err(error @ WinFileInfoError::NonUtf8Output { .. }) => assert_eq!(error.stderr(), Some("chcp failed")),
+            other => panic!("expected NonUtf8Output, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_posix_paths_are_translated(){
+        let runner = FixtureRunner::new().with_stdout(ENTITY_ONEDRIVE).with_stdout("[]").with_stdout("[]").with_stdout("[]");
+        let options = QueryOptions { translate_posix_paths: true, ..QueryOptions::default() };
+        let wsl_options = QueryOptions { wsl_distro: Some("Ubuntu".to_string()), ..options.clone() };
+
+        entity_info_with_options(&runner, "/mnt/c/Users/necoo/Desktop/OneDrive", &options).unwrap();
+        other_folder_info_with_options(&runner, "/home/necoo", &wsl_options).unwrap();
+        entities_info_with_options(&runner, &["/cygdrive/d/data", "/c/Users"], &options);
+        // translation is off by default:
+        other_folder_info_with(&runner, "/c/Users").unwrap();
+        let commands = runner.commands();
+
+        assert!(commands[0].starts_with("Get-Item -LiteralPath 'C:\\Users\\necoo\\Desktop\\OneDrive' |"));
+        assert!(commands[1].starts_with("Get-ChildItem -LiteralPath '\\\\wsl.localhost\\Ubuntu\\home\\necoo' |"));
+        assert!(commands[2].contains("$paths = @('D:\\data','C:\\Users');"));
+        assert!(commands[3].starts_with("Get-ChildItem -LiteralPath '/c/Users' |"));
+
+        let runner = FixtureRunner::new().with_stdout("[]").with_stdout("");
+
+        find_entities_with_options(&runner, "*.rs", "/mnt/d/projects", &options).unwrap();
+        let missing = find_entity_with_options(&runner, "main.rs", "/home/necoo/projects", &wsl_options);
+        let commands = runner.commands();
+
+        assert!(commands[0].starts_with("Get-ChildItem -LiteralPath 'D:\\projects' -Filter '*.rs' -Recurse |"));
+        assert!(commands[1].starts_with("Get-ChildItem -LiteralPath '\\\\wsl.localhost\\Ubuntu\\home\\necoo\\projects' -Filter 'main.rs' -Recurse |"));
+        assert!(matches!(missing, Err(WinFileInfoError::PathNotFound { path, .. }) if path == "\\\\wsl.localhost\\Ubuntu\\home\\necoo\\projects\\**\\main.rs"));
+    }
+
+    #[test]
+    fn test_paths_are_expanded(){
+        let runner = FixtureRunner::new().with_stdout(ENTITY_ONEDRIVE).with_stdout("[]");
+        let environment = Environment::new().with_variable("USERPROFILE", "D:\\Users\\necoo").with_known_folder(KnownFolder::Desktop, "D:\\Users\\necoo\\OneDrive\\Desktop");
+        let options = QueryOptions { environment: Some(environment), ..QueryOptions::default() };
+
+        entity_info_with_options(&runner, "shell:Desktop\\OneDrive", &options).unwrap();
+        let results = entities_info_with_options(&runner, &["%USERPROFILE%\\a", "shell:Pictures\\b", "$env:UserProfile\\c"], &options);
+        let commands = runner.commands();
+
+        assert!(commands[0].starts_with("Get-Item -LiteralPath 'D:\\Users\\necoo\\OneDrive\\Desktop\\OneDrive' |"));
+        assert!(commands[1].contains("$paths = @('D:\\Users\\necoo\\a','D:\\Users\\necoo\\c');"));
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[1], Err(WinFileInfoError::InvalidPath { .. })));
+        assert!(matches!(results[2], Err(WinFileInfoError::UnparseableRecord { .. })));
+        // nothing runs for a path that can't be expanded:
+        assert!(other_folder_info_with_options(&runner, "shell:Nope", &options).is_err());
+        assert_eq!(runner.commands().len(), 2);
+
+        let runner = FixtureRunner::new().with_stdout("[]").with_stdout("").with_stdout("1042");
+
+        find_entities_with_options(&runner, "*.docx", "shell:Desktop", &options).unwrap();
+        let missing = find_entity_with_options(&runner, "thesis.docx", "$env:USERPROFILE\\Documents", &options);
+        let kind = entity_kind_with_options(&runner, "%USERPROFILE%\\OneDrive", &options).unwrap();
+        let commands = runner.commands();
+
+        assert!(commands[0].starts_with("Get-ChildItem -LiteralPath 'D:\\Users\\necoo\\OneDrive\\Desktop' -Filter '*.docx' -Recurse |"));
+        assert!(commands[1].starts_with("Get-ChildItem -LiteralPath 'D:\\Users\\necoo\\Documents' -Filter 'thesis.docx' -Recurse |"));
+        assert!(matches!(missing, Err(WinFileInfoError::PathNotFound { path, .. }) if path == "D:\\Users\\necoo\\Documents\\**\\thesis.docx"));
+        assert!(commands[2].starts_with("Get-Item -LiteralPath 'D:\\Users\\necoo\\OneDrive' -Force |"));
+        assert!(kind.is_dir() && kind.is_reparse());
+        assert!(matches!(entity_kind_with_options(&runner, "shell:Nope", &options), Err(WinFileInfoError::InvalidPath { .. })));
+        assert_eq!(runner.commands().len(), 3);
+    }
+
+    #[test]
+    fn test_hostile_names_are_quoted(){
+        let runner = FixtureRunner::new().with_stdout("").with_stdout("").with_stdout("16").with_stdout("[]");
+        let hostile = "C:\\x'; Remove-Item C:\\ -Recurse; '";
+
+        let _ = other_folder_info_with(&runner, hostile);
+        let _ = find_entities_with(&runner, "\u{2019}; Stop-Computer; \u{2018}*", "C:\\it's mine");
+        let _ = entity_kind_with(&runner, "C:\\[1] it's.txt");
+        let _ = entities_info_with(&runner, &[hostile]);
+        let commands = runner.commands();
+
+        assert!(commands[0].starts_with("Get-ChildItem -LiteralPath 'C:\\x''; Remove-Item C:\\ -Recurse; ''' |"));
+        assert!(commands[1].starts_with("Get-ChildItem -LiteralPath 'C:\\it''s mine' -Filter '\u{2019}\u{2019}; Stop-Computer; \u{2018}\u{2018}*' -Recurse |"));
+        assert!(commands[2].starts_with("Get-Item -LiteralPath 'C:\\[1] it''s.txt' -Force |"));
+        assert!(commands[3].contains("$paths = @('C:\\x''; Remove-Item C:\\ -Recurse; ''');"));
+    }
+
+    #[test]
+    fn test_entity_kind_of_windows_entity(){
+        let runner = FixtureRunner::new().with_stdout(ENTITY_ONEDRIVE);
+
+        let kind = entity_info_with(&runner, "C:\\Users\\necoo\\Desktop\\OneDrive").unwrap().kind();
+
+        assert!(kind.is_dir() && kind.is_reparse() && kind.is_hidden() && kind.is_system());
+    }
+
+    #[test]
+    fn test_entities_by_path_key(){
+        let runner = FixtureRunner::new().with_stdout(ENTITY_ONEDRIVE).with_stdout(ENTITY_ONEDRIVE);
+        let mut entities = std::collections::BTreeMap::new();
+
+        for path in ["C:\\Users\\necoo\\Desktop\\OneDrive", "c:/users/necoo/desktop/onedrive"] {
+            let entity = entity_info_with(&runner, path).unwrap();
+            let mut changed = entity.clone();
+            changed.absolute_path = changed.absolute_path.to_lowercase().replace('\\', "/");
+
+            entities.insert(entity.path_key(), entity);
+            entities.insert(changed.path_key(), changed);
+        }
+
+        assert_eq!(entities.len(), 1);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_is_functions_with_fixture(){
+        let runner = FixtureRunner::new();
+        let runner = (0..7).fold(runner, |runner, _| runner.with_stdout("1042"));
+        let path = "C:\\Users\\necoo\\Desktop\\OneDrive";
+
+        assert!(is_directory_with(&runner, path));
+        assert!(!is_archive_with(&runner, path));
+        assert!(is_reparse_point_or_symlink_with(&runner, path));
+        assert!(!is_directory_and_archive_with(&runner, path));
+        assert!(is_directory_and_reparse_point_or_symlink_with(&runner, path));
+        assert!(!is_archive_and_reparse_point_or_symlink_with(&runner, path));
+        assert!(!is_directory_and_archive_and_reparse_point_or_symlink_with(&runner, path));
+
+        // a missing entity is none of them:
+        assert!(!is_directory_with(&FixtureRunner::new().with_stdout(""), path));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::path::{PathPrefix, WindowsPath};
+
+// paths this long go to powershell in their verbatim form. windows limits the paths to "MAX_PATH" and the
+// directories to 12 characters less than that, so their "8.3" children still fit.
+pub const LONG_PATH_THRESHOLD: usize = crate::validation::MAX_PATH - 12;
+
+fn windows_length(path: &str) -> usize {
+    path.encode_utf16().count()
+}
+
+// "\\?\C:\..." or "\\?\UNC\server\share\..." form of a long absolute path, so windows doesn't stop at "MAX_PATH"
+// with it. the path is normalized first since windows doesn't do it for verbatim paths. short, relative, verbatim
+// and device paths are given as they are:
+//
+// let path = format!("C:\\build\\{}\\index.js", "node_modules\\a\\".repeat(20));
+//
+// assert!(promote_long_path(&path).starts_with("\\\\?\\C:\\build\\node_modules"));
+pub fn promote_long_path(path: &str) -> String {
+    if windows_length(path) < LONG_PATH_THRESHOLD {
+        return path.to_string();
+    }
+
+    let parsed = match WindowsPath::parse(path) {
+        Ok(parsed) => parsed,
+        Err(_) => return path.to_string()
+    };
+
+    match parsed.prefix() {
+        Some(PathPrefix::Disk(_)) | Some(PathPrefix::Unc { .. }) => parsed.to_verbatim().map(|verbatim| verbatim.to_string()).unwrap_or(path.to_string()),
+        _ => path.to_string()
+    }
+}
+
+// the usual form of a verbatim disk or unc path, like powershell gives it back for a promoted path. it's left
+// as it is when the usual form would point to somewhere else, since windows normalizes that form: components
+// like "." and "..", components which end with a dot or a space, and "/" in a component.
+pub fn strip_verbatim_prefix(path: &str) -> String {
+    let parsed = match WindowsPath::parse(path) {
+        Ok(parsed) => parsed,
+        Err(_) => return path.to_string()
+    };
+
+    if !matches!(parsed.prefix(), Some(PathPrefix::VerbatimDisk(_)) | Some(PathPrefix::VerbatimUnc { .. })) {
+        return path.to_string();
+    }
+
+    let safe = parsed.components().iter().all(|component| component != "." && component != ".." && !component.ends_with(['.', ' ']) && !component.contains('/'));
+
+    match (safe, parsed.to_win32()) {
+        (true, Some(win32)) => win32.to_string(),
+        _ => path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a path of given utf-16 length under "root", made of components that look like a node_modules tree.
+    fn synthetic_path(root: &str, length: usize) -> String {
+        let mut path = root.to_string();
+        let mut index = 0;
+
+        while windows_length(&path) < length {
+            path.push_str(&format!("\\node_modules\\pkg-{}", index));
+            index += 1;
+        }
+
+        // the components are ascii, so the extra units are bytes at the end.
+        path.truncate(path.len() - (windows_length(&path) - length));
+
+        path.trim_end_matches('\\').to_string()
+    }
+
+    #[test]
+    fn test_short_paths_stay(){
+        for path in ["C:\\Users\\necoo", "\\\\server\\share\\docs", "relative\\path", "\\\\?\\C:\\already", "\\\\.\\COM1", "/mnt/c/Users"] {
+            assert_eq!(promote_long_path(path), path);
+        }
+
+        assert_eq!(promote_long_path(&"x".repeat(400)), "x".repeat(400));
+        assert_eq!(promote_long_path(&format!("\\\\.\\C:\\{}", "a".repeat(400))), format!("\\\\.\\C:\\{}", "a".repeat(400)));
+    }
+
+    #[test]
+    fn test_long_paths_are_promoted(){
+        let disk = synthetic_path("C:\\build", 400);
+        let unc = synthetic_path("\\\\buildserver\\builds", 400);
+
+        assert_eq!(disk.len(), 400);
+        assert_eq!(promote_long_path(&disk), format!("\\\\?\\{}", disk));
+        assert_eq!(promote_long_path(&unc), format!("\\\\?\\UNC\\{}", &unc[2..]));
+        assert_eq!(promote_long_path(&disk.replace('\\', "/")), format!("\\\\?\\{}", disk));
+
+        // they are normalized first, windows wouldn't do it after "\\?\":
+        let with_dots = format!("{}\\.\\extra\\..\\index.js", disk);
+
+        assert_eq!(promote_long_path(&with_dots), format!("\\\\?\\{}\\index.js", disk));
+    }
+
+    #[test]
+    fn test_round_trip(){
+        for length in [LONG_PATH_THRESHOLD, 300, 400, 1000, 5000] {
+            for root in ["C:\\build", "\\\\buildserver\\builds", "D:\\çalışma 日本語"] {
+                let path = synthetic_path(root, length);
+                let promoted = promote_long_path(&path);
+
+                assert!(promoted.starts_with("\\\\?\\"), "{}", length);
+                assert_eq!(strip_verbatim_prefix(&promoted), path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsafe_verbatim_paths_stay(){
+        let long = "a".repeat(300);
+
+        for path in [format!("\\\\?\\C:\\{}\\trailing.", long), format!("\\\\?\\C:\\{}\\..\\b", long), format!("\\\\?\\C:\\{}\\a/b", long), "\\\\?\\Volume{1234}\\a".to_string(), "\\\\?\\GLOBALROOT\\Device\\X".to_string()] {
+            assert_eq!(strip_verbatim_prefix(&path), path);
+        }
+
+        assert_eq!(strip_verbatim_prefix("\\\\?\\C:\\"), "C:\\");
+        assert_eq!(strip_verbatim_prefix("C:\\Users"), "C:\\Users");
+    }
+}
@@ -0,0 +1,503 @@
+use std::{env::current_dir, fs::{self, Metadata}, io, path::{self, Path, PathBuf}};
+
+use crate::{acl::{AccessControlEntry, AceFlags, AceType, EntityAcl, FileSystemRights}, check_entity_permissions, sid::Sid, DirectorySize, EntityKind, FileAttributes, QueryOptions, WildcardFilter, WinFileInfoError, WindowsEntity, WindowsTimestamp};
+
+// backend that reads the entities via "std::fs" instead of powershell, so it works on every platform and it's
+// way faster since it doesn't start any process. on windows the attributes come from the file system itself, on
+// other platforms they are mapped from what the platform has:
+//
+// - directories get "Directory" and the other entities get "Archive", like windows does for new files.
+// - entities with no write permission for anyone get "ReadOnly".
+// - dotfiles get "Hidden".
+// - symlinks get "ReparsePoint", plus "Directory" if they point to a directory.
+//
+// owner is the user name of the uid on unix and empty on windows, since reading the owner there needs the
+// security apis. the sid of the owner is "S-1-22-1-uid" on unix, like samba gives it.
+//
+// sample use:
+//
+// let entities = native::other_folder_info("/home/necoo/projects").unwrap();
+
+pub fn current_folder_info() -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    let current_path = current_dir().map_err(WinFileInfoError::Io)?;
+
+    match current_path.to_str() {
+        Some(current_path) => other_folder_info(current_path),
+        None => Err(WinFileInfoError::Io(io::Error::new(io::ErrorKind::InvalidData, "current directory is not valid unicode")))
+    }
+}
+
+pub fn other_folder_info(path: &str) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    other_folder_info_with_options(path, &QueryOptions::default())
+}
+
+// like "Get-ChildItem", gives the entity itself if the path is not a directory.
+pub fn other_folder_info_with_options(path: &str, options: &QueryOptions) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    let path = &crate::expanded_path(path, options)?;
+    let folder = absolute_path(path)?;
+
+    if !fs::metadata(&folder).map_err(|error| from_io(path, error))?.is_dir() {
+        return Ok(vec![create_windows_entity(&folder, options).map_err(|error| from_io(path, error))?]);
+    }
+
+    let mut entities = vec![];
+
+    for entry in fs::read_dir(&folder).map_err(|error| from_io(path, error))? {
+        let entry = entry.map_err(|error| from_io(path, error))?;
+
+        entities.push(create_windows_entity(&entry.path(), options).map_err(|error| from_io(&entry.path().to_string_lossy(), error))?);
+    }
+
+    entities.sort_by_key(|entity| entity.name.to_lowercase());
+
+    Ok(entities)
+}
+
+pub fn entity_info(path: &str) -> Result<WindowsEntity, WinFileInfoError> {
+    entity_info_with_options(path, &QueryOptions::default())
+}
+
+pub fn entity_info_with_options(path: &str, options: &QueryOptions) -> Result<WindowsEntity, WinFileInfoError> {
+    let path = &crate::expanded_path(path, options)?;
+    create_windows_entity(&absolute_path(path)?, options).map_err(|error| from_io(path, error))
+}
+
+// every path is read on its own, there is no process to save here.
+pub fn entities_info(paths: &[&str]) -> Vec<Result<WindowsEntity, WinFileInfoError>> {
+    paths.iter().map(|path| entity_info(path)).collect()
+}
+
+pub fn find_entity(name: &str, search_dest: &str) -> Result<WindowsEntity, WinFileInfoError> {
+    find_entity_with_options(name, search_dest, &QueryOptions::default())
+}
+
+pub fn find_entity_with_options(name: &str, search_dest: &str, options: &QueryOptions) -> Result<WindowsEntity, WinFileInfoError> {
+    match find_entities_with_options(name, search_dest, options)?.into_iter().next() {
+        Some(entity) => Ok(entity),
+        None => Err(WinFileInfoError::PathNotFound { path: Path::new(&crate::expanded_path(search_dest, options)?).join("**").join(name).to_string_lossy().to_string(), stderr: None })
+    }
+}
+
+// searches every entity under "search_dest" whose name matches "name", which is matched like the "-Filter" of
+// "Get-ChildItem" without the short names, see "WildcardFilter". it doesn't follow the symlinks and skips the directories which it can't
+// read, like powershell does.
+pub fn find_entities(name: &str, search_dest: &str) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    find_entities_with_options(name, search_dest, &QueryOptions::default())
+}
+
+pub fn find_entities_with_options(name: &str, search_dest: &str, options: &QueryOptions) -> Result<Vec<WindowsEntity>, WinFileInfoError> {
+    let search_dest = &crate::expanded_path(search_dest, options)?;
+    let root = absolute_path(search_dest)?;
+    let filter = WildcardFilter::new(name);
+    let mut entities = vec![];
+    let mut folders = vec![root.clone()];
+
+    fs::read_dir(&root).map_err(|error| from_io(search_dest, error))?;
+
+    while let Some(folder) = folders.pop() {
+        let entries = match fs::read_dir(&folder) {
+            Ok(entries) => entries,
+            Err(_) => continue
+        };
+
+        let mut children: Vec<PathBuf> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+        children.sort();
+
+        for child in children {
+            let metadata = match fs::symlink_metadata(&child) {
+                Ok(metadata) => metadata,
+                Err(_) => continue
+            };
+
+            if filter.matches(&entity_name(&child)) {
+                if let Ok(entity) = create_windows_entity(&child, options) {
+                    entities.push(entity);
+                }
+            }
+
+            if metadata.is_dir() {
+                folders.push(child);
+            }
+        }
+    }
+
+    Ok(entities)
+}
+
+pub fn entity_kind(path: &str) -> Result<EntityKind, WinFileInfoError> {
+    entity_kind_with_options(path, &QueryOptions::default())
+}
+
+pub fn entity_kind_with_options(path: &str, options: &QueryOptions) -> Result<EntityKind, WinFileInfoError> {
+    let path = &crate::expanded_path(path, options)?;
+    let path_buf = absolute_path(path)?;
+    let metadata = fs::symlink_metadata(&path_buf).map_err(|error| from_io(path, error))?;
+
+    Ok(EntityKind::from_attributes(entity_attributes(&path_buf, &metadata)))
+}
+
+// an acl which gives the same access with the permission bits, an allow ace for each of the owner, the group and
+// "Everyone". unix has no inheritance, so the aces have no flags.
+pub fn entity_acl(path: &str) -> Result<EntityAcl, WinFileInfoError> {
+    let metadata = fs::symlink_metadata(absolute_path(path)?).map_err(|error| from_io(path, error))?;
+
+    Ok(unix_acl(&metadata))
+}
+
+fn from_io(path: &str, error: io::Error) -> WinFileInfoError {
+    match error.kind() {
+        io::ErrorKind::NotFound => WinFileInfoError::PathNotFound { path: path.to_string(), stderr: None },
+        io::ErrorKind::PermissionDenied => WinFileInfoError::AccessDenied { path: path.to_string(), stderr: None },
+        _ => WinFileInfoError::Io(error)
+    }
+}
+
+// doesn't resolve the symlinks, unlike "canonicalize", otherwise we couldn't tell that the entity is a symlink.
+fn absolute_path(path: &str) -> Result<PathBuf, WinFileInfoError> {
+    path::absolute(path).map_err(|error| from_io(path, error))
+}
+
+fn entity_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => path.to_string_lossy().to_string()
+    }
+}
+
+fn create_windows_entity(path: &Path, options: &QueryOptions) -> Result<WindowsEntity, io::Error> {
+    let metadata = fs::symlink_metadata(path)?;
+    let attributes = entity_attributes(path, &metadata);
+
+    // sizes of the symlinks are the sizes of their targets, when the target is there.
+    let size = match attributes.contains(FileAttributes::DIRECTORY) {
+        true => None,
+        false => Some(fs::metadata(path).unwrap_or(metadata.clone()).len())
+    };
+
+    let directory_size = match options.directory_sizes && attributes.contains(FileAttributes::DIRECTORY) && !metadata.is_symlink() {
+        true => Some(directory_size(path)),
+        false => None
+    };
+
+    let entity_permissions = check_entity_permissions(&mode_string(attributes));
+
+    Ok(WindowsEntity {
+        mode: entity_permissions.permissions.into_iter().map(|x| x.to_string()).collect(),
+        types: entity_permissions.entity_types.into_iter().map(|x| x.to_string()).collect(),
+        owner: owner(&metadata),
+        owner_sid: owner_sid(&metadata),
+        last_write_time: metadata.modified().ok().map(WindowsTimestamp::from_system_time),
+        name: entity_name(path),
+        creation_time: metadata.created().ok().map(WindowsTimestamp::from_system_time),
+        attributes,
+        last_access_time: metadata.accessed().ok().map(WindowsTimestamp::from_system_time),
+        size,
+        absolute_path: path.to_string_lossy().to_string(),
+        directory_size
+    })
+}
+
+// the "Mode" column of powershell, so the entities of that backend get the same "mode" and "types".
+fn mode_string(attributes: FileAttributes) -> String {
+    [
+        (FileAttributes::DIRECTORY, 'd'),
+        (FileAttributes::ARCHIVE, 'a'),
+        (FileAttributes::READONLY, 'r'),
+        (FileAttributes::HIDDEN, 'h'),
+        (FileAttributes::SYSTEM, 's'),
+        (FileAttributes::REPARSE_POINT, 'l')
+    ].iter().map(|(flag, letter)| if attributes.contains(*flag) { *letter } else { '-' }).collect()
+}
+
+#[cfg(windows)]
+fn entity_attributes(_path: &Path, metadata: &Metadata) -> FileAttributes {
+    use std::os::windows::fs::MetadataExt;
+
+    FileAttributes::from_bits_retain(metadata.file_attributes())
+}
+
+#[cfg(not(windows))]
+fn entity_attributes(path: &Path, metadata: &Metadata) -> FileAttributes {
+    let mut attributes = FileAttributes::empty();
+
+    if metadata.is_symlink() {
+        attributes.insert(FileAttributes::REPARSE_POINT);
+    }
+
+    match fs::metadata(path).map(|target| target.is_dir()).unwrap_or(metadata.is_dir()) {
+        true => attributes.insert(FileAttributes::DIRECTORY),
+        false => attributes.insert(FileAttributes::ARCHIVE)
+    }
+
+    attributes.set(FileAttributes::READONLY, metadata.permissions().readonly());
+    attributes.set(FileAttributes::HIDDEN, entity_name(path).starts_with('.'));
+
+    attributes
+}
+
+#[cfg(unix)]
+fn owner(metadata: &Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+
+    user_name(metadata.uid()).unwrap_or(metadata.uid().to_string())
+}
+
+#[cfg(not(unix))]
+fn owner(_metadata: &Metadata) -> String {
+    "".to_string()
+}
+
+// the uids are mapped to "S-1-22-1-uid" sids, like samba shows the unix users to windows.
+#[cfg(unix)]
+fn owner_sid(metadata: &Metadata) -> Option<Sid> {
+    use std::os::unix::fs::MetadataExt;
+
+    Sid::new(22, &[1, metadata.uid()]).ok()
+}
+
+#[cfg(not(unix))]
+fn owner_sid(_metadata: &Metadata) -> Option<Sid> {
+    None
+}
+
+// looks the uid up in "/etc/passwd", which is enough for local users without linking to libc.
+#[cfg(unix)]
+fn user_name(uid: u32) -> Option<String> {
+    account_name("/etc/passwd", uid)
+}
+
+// "/etc/group" has the same "name:password:id" start with "/etc/passwd".
+#[cfg(unix)]
+fn group_name(gid: u32) -> Option<String> {
+    account_name("/etc/group", gid)
+}
+
+#[cfg(unix)]
+fn account_name(database: &str, id: u32) -> Option<String> {
+    let accounts = fs::read_to_string(database).ok()?;
+
+    accounts.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+
+        match fields.get(2).and_then(|field| field.parse::<u32>().ok()) {
+            Some(account_id) if account_id == id => Some(fields[0].to_string()),
+            _ => None
+        }
+    })
+}
+
+#[cfg(unix)]
+fn unix_acl(metadata: &Metadata) -> EntityAcl {
+    use std::os::unix::fs::MetadataExt;
+
+    let owner = owner(metadata);
+    let group = group_name(metadata.gid()).unwrap_or(metadata.gid().to_string());
+
+    // "rwx" of a class, "r" reads the data and the attributes, "w" writes them and "x" executes or traverses.
+    let class_rights = |bits: u32| {
+        let mut rights = FileSystemRights::empty();
+
+        for (bit, mask) in [(0o4, FileSystemRights::READ), (0o2, FileSystemRights::WRITE), (0o1, FileSystemRights::EXECUTE_FILE)] {
+            if bits & bit != 0 {
+                rights.insert(mask);
+            }
+        }
+
+        // windows gives "Synchronize" with any of them.
+        if bits & 0o7 != 0 {
+            rights.insert(FileSystemRights::SYNCHRONIZE);
+        }
+
+        rights
+    };
+
+    let access = [(owner.clone(), metadata.mode() >> 6), (group.clone(), metadata.mode() >> 3), ("Everyone".to_string(), metadata.mode())].into_iter()
+        .map(|(trustee, bits)| AccessControlEntry::new(AceType::Allow, trustee, class_rights(bits & 0o7), AceFlags::empty()))
+        .filter(|entry| !entry.rights.is_empty())
+        .collect();
+
+    EntityAcl { owner, group, access, ..EntityAcl::default() }
+}
+
+// reading the acl needs the security apis there, like the owner.
+#[cfg(not(unix))]
+fn unix_acl(_metadata: &Metadata) -> EntityAcl {
+    EntityAcl::default()
+}
+
+// walks the tree without following the symlinks and skips what it can't read, like the powershell query does.
+fn directory_size(path: &Path) -> DirectorySize {
+    let mut size = DirectorySize::default();
+    let mut folders = vec![path.to_path_buf()];
+
+    while let Some(folder) = folders.pop() {
+        let entries = match fs::read_dir(&folder) {
+            Ok(entries) => entries,
+            Err(_) => continue
+        };
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => folders.push(entry.path()),
+                Ok(metadata) if metadata.is_file() => {
+                    size.logical += metadata.len();
+                    size.allocated += allocated_size(&metadata);
+                },
+                _ => ()
+            }
+        }
+    }
+
+    size
+}
+
+#[cfg(unix)]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.blocks() * 512
+}
+
+// same fallback with the powershell query, when we don't know the cluster size.
+#[cfg(not(unix))]
+fn allocated_size(metadata: &Metadata) -> u64 {
+    metadata.len().div_ceil(4096) * 4096
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+
+    // every test gets its own folder under the temp directory, which is deleted when the test ends.
+    struct TestFolder(PathBuf);
+
+    impl TestFolder {
+        fn new(name: &str) -> TestFolder {
+            let path = std::env::temp_dir().join(format!("win-file-info-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+
+            TestFolder(path)
+        }
+
+        fn path(&self, name: &str) -> String {
+            self.0.join(name).to_string_lossy().to_string()
+        }
+    }
+
+    impl Drop for TestFolder {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_other_folder_info(){
+        let folder = TestFolder::new("listing");
+        fs::create_dir(folder.path("projects")).unwrap();
+        fs::write(folder.path("notes.txt"), "hello").unwrap();
+        fs::write(folder.path(".profile"), "").unwrap();
+        std::os::unix::fs::symlink(folder.path("projects"), folder.path("link")).unwrap();
+
+        let entities = other_folder_info(&folder.path("")).unwrap();
+        let names: Vec<&str> = entities.iter().map(|entity| entity.name.as_str()).collect();
+
+        assert_eq!(names, vec![".profile", "link", "notes.txt", "projects"]);
+        assert!(entities[0].kind().is_hidden() && entities[0].kind().is_file());
+        assert_eq!(entities[1].types, vec!["directory", "reparse-point-or-symlink"]);
+        assert_eq!(entities[2].size, Some(5));
+        assert_eq!(entities[2].mode, vec!["archive"]);
+        assert_eq!(entities[2].absolute_path, folder.path("notes.txt"));
+        assert!(entities[2].last_write_time.is_some());
+        assert!(!entities[2].owner.is_empty());
+        assert_eq!(entities[2].owner_sid.as_ref().unwrap().sub_authorities()[0], 1);
+        assert_eq!(entities[3].size, None);
+    }
+
+    #[test]
+    fn test_entity_info_and_kind(){
+        use std::os::unix::fs::PermissionsExt;
+
+        let folder = TestFolder::new("entity");
+        fs::write(folder.path("locked.txt"), "").unwrap();
+        fs::set_permissions(folder.path("locked.txt"), fs::Permissions::from_mode(0o444)).unwrap();
+
+        let entity = entity_info(&folder.path("locked.txt")).unwrap();
+
+        assert_eq!(entity.attributes, FileAttributes::ARCHIVE | FileAttributes::READONLY);
+        assert!(entity_kind(&folder.path("")).unwrap().is_dir());
+        assert!(matches!(entity_info(&folder.path("missing")), Err(WinFileInfoError::PathNotFound { .. })));
+        assert!(matches!(other_folder_info(&folder.path("missing")), Err(WinFileInfoError::PathNotFound { .. })));
+
+        let results = entities_info(&[&folder.path("missing"), &folder.path("locked.txt")]);
+        assert!(results[0].is_err() && results[1].is_ok());
+    }
+
+    #[test]
+    fn test_entity_acl(){
+        use std::os::unix::fs::PermissionsExt;
+
+        let folder = TestFolder::new("acl");
+        fs::write(folder.path("script.sh"), "").unwrap();
+        fs::set_permissions(folder.path("script.sh"), fs::Permissions::from_mode(0o750)).unwrap();
+
+        let acl = entity_acl(&folder.path("script.sh")).unwrap();
+        let rights: Vec<String> = acl.access.iter().map(|entry| entry.rights.to_string()).collect();
+
+        assert_eq!(acl.access.len(), 2);
+        assert_eq!(acl.access[0].trustee, acl.owner);
+        assert_eq!(acl.access[1].trustee, acl.group);
+        assert_eq!(rights, ["Write, ReadAndExecute, Synchronize", "ReadAndExecute, Synchronize"]);
+
+        // the classes without "x" keep "Synchronize" too:
+        for (mode, masks) in [(0o644, [0x120116 | 0x20089, 0x120089, 0x120089]), (0o640, [0x120116 | 0x20089, 0x120089, 0])] {
+            fs::set_permissions(folder.path("script.sh"), fs::Permissions::from_mode(mode)).unwrap();
+
+            let acl = entity_acl(&folder.path("script.sh")).unwrap();
+            let bits: Vec<u32> = acl.access.iter().map(|entry| entry.rights.bits()).collect();
+
+            assert_eq!(bits, masks.into_iter().filter(|mask| *mask != 0).collect::<Vec<u32>>());
+        }
+
+        assert!(matches!(entity_acl(&folder.path("missing")), Err(WinFileInfoError::PathNotFound { .. })));
+    }
+
+    #[test]
+    fn test_find_entities(){
+        let folder = TestFolder::new("find");
+        fs::create_dir_all(folder.path("a/b")).unwrap();
+        fs::write(folder.path("a/Cargo.toml"), "").unwrap();
+        fs::write(folder.path("a/b/cargo.toml"), "").unwrap();
+        fs::write(folder.path("a/b/main.rs"), "").unwrap();
+
+        assert_eq!(find_entities("Cargo.toml", &folder.path("")).unwrap().len(), 2);
+        assert_eq!(find_entities("*.rs", &folder.path("")).unwrap()[0].name, "main.rs");
+        assert_eq!(find_entities("?", &folder.path("")).unwrap().len(), 2);
+        assert!(matches!(find_entity("*.exe", &folder.path("")), Err(WinFileInfoError::PathNotFound { .. })));
+
+        // the search path and the path of "entity_kind" are expanded:
+        let options = QueryOptions { environment: Some(crate::Environment::new().with_variable("PROJECTS", &folder.path("a"))), ..QueryOptions::default() };
+
+        assert_eq!(find_entities_with_options("*.rs", "%PROJECTS%", &options).unwrap()[0].name, "main.rs");
+        assert_eq!(find_entity_with_options("*.toml", "$env:PROJECTS", &options).unwrap().name, "Cargo.toml");
+        assert!(entity_kind_with_options("%PROJECTS%", &options).unwrap().is_dir());
+        assert!(matches!(entity_kind_with_options("%NOPE%", &options), Err(WinFileInfoError::PathNotFound { .. })));
+        assert!(matches!(entity_kind_with_options("shell:Nope", &options), Err(WinFileInfoError::InvalidPath { .. })));
+    }
+
+    #[test]
+    fn test_directory_sizes(){
+        let folder = TestFolder::new("sizes");
+        fs::create_dir_all(folder.path("videos/2026")).unwrap();
+        fs::write(folder.path("videos/a.mp4"), vec![0; 5000]).unwrap();
+        fs::write(folder.path("videos/2026/b.mp4"), vec![0; 3]).unwrap();
+
+        let options = QueryOptions { directory_sizes: true, ..QueryOptions::default() };
+        let entity = entity_info_with_options(&folder.path("videos"), &options).unwrap();
+
+        assert_eq!(entity.directory_size.unwrap().logical, 5003);
+        assert!(entity.directory_size.unwrap().allocated >= 5003);
+        assert_eq!(entity_info(&folder.path("videos")).unwrap().directory_size, None);
+    }
+}
@@ -0,0 +1,491 @@
+use std::{fmt, str::FromStr};
+
+use crate::{error::WinFileInfoError, key::upcase};
+
+// the part of a windows path which comes before its components.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathPrefix {
+    // "C:", both "C:\foo" and "C:foo" have it.
+    Disk(char),
+    // "\\server\share"
+    Unc { server: String, share: String },
+    // "\\?\C:"
+    VerbatimDisk(char),
+    // "\\?\UNC\server\share"
+    VerbatimUnc { server: String, share: String },
+    // "\\?\Volume{...}" and the other verbatim paths which are not a disk or an unc share.
+    Verbatim(String),
+    // "\\.\COM1", "\\.\PhysicalDrive0" or "\\.\C:"
+    Device(String)
+}
+
+// a windows path, parsed to its prefix and components. it's pure string logic, so it works on every platform
+// and never touches the file system:
+//
+// let path = WindowsPath::parse("C:/Users\\necoo/./Desktop\\..\\notes.txt").unwrap().normalize();
+//
+// assert_eq!(path.to_string(), "C:\\Users\\necoo\\notes.txt");
+// assert_eq!(path.to_verbatim().unwrap().to_string(), "\\\\?\\C:\\Users\\necoo\\notes.txt");
+//
+// "/" is a separator too, except in verbatim paths which windows passes to the file system as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WindowsPath {
+    prefix: Option<PathPrefix>,
+    has_root: bool,
+    components: Vec<String>
+}
+
+fn is_separator(character: char) -> bool {
+    character == '\\' || character == '/'
+}
+
+// "C:" at the start of the path.
+fn drive_letter(path: &str) -> Option<char> {
+    let mut chars = path.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic() => Some(letter),
+        _ => None
+    }
+}
+
+fn split_components(path: &str, separator: fn(char) -> bool) -> Vec<String> {
+    path.split(separator).filter(|component| !component.is_empty()).map(|component| component.to_string()).collect()
+}
+
+// "server\share\rest" of an unc path.
+fn parse_unc(input: &str, rest: &str, separator: fn(char) -> bool) -> Result<(String, String, Vec<String>), WinFileInfoError> {
+    let mut components = split_components(rest, separator);
+
+    match components.len() {
+        0 => Err(WinFileInfoError::invalid_path(input, "unc path has no server")),
+        1 => Err(WinFileInfoError::invalid_path(input, "unc path has no share")),
+        _ => {
+            let rest = components.split_off(2);
+            let share = components.pop().unwrap_or_default();
+            let server = components.pop().unwrap_or_default();
+
+            Ok((server, share, rest))
+        }
+    }
+}
+
+impl WindowsPath {
+    pub fn parse(input: &str) -> Result<WindowsPath, WinFileInfoError> {
+        // verbatim paths only take backslashes, "\\?\" turns off every normalization of windows.
+        if let Some(rest) = input.strip_prefix("\\\\?\\") {
+            let verbatim_separator: fn(char) -> bool = |character| character == '\\';
+
+            if rest.get(..3).is_some_and(|unc| unc.eq_ignore_ascii_case("UNC")) && (rest.len() == 3 || rest[3..].starts_with('\\')) {
+                let (server, share, components) = parse_unc(input, &rest[3..], verbatim_separator)?;
+
+                return Ok(WindowsPath { prefix: Some(PathPrefix::VerbatimUnc { server, share }), has_root: true, components });
+            }
+
+            if let Some(letter) = drive_letter(rest) {
+                if rest.len() == 2 || rest[2..].starts_with('\\') {
+                    return Ok(WindowsPath { prefix: Some(PathPrefix::VerbatimDisk(letter)), has_root: true, components: split_components(&rest[2..], verbatim_separator) });
+                }
+            }
+
+            let mut components = split_components(rest, verbatim_separator);
+
+            if components.is_empty() {
+                return Err(WinFileInfoError::invalid_path(input, "verbatim path has nothing after its prefix"));
+            }
+
+            let name = components.remove(0);
+
+            return Ok(WindowsPath { prefix: Some(PathPrefix::Verbatim(name)), has_root: true, components });
+        }
+
+        let mut chars = input.chars();
+
+        if let (Some(first), Some(second)) = (chars.next(), chars.next()) {
+            if is_separator(first) && is_separator(second) {
+                let rest = &input[2..];
+                let mut rest_chars = rest.chars();
+
+                // "\\.\" is the device namespace.
+                if let (Some('.'), Some(separator)) = (rest_chars.next(), rest_chars.next()) {
+                    if is_separator(separator) {
+                        let mut components = split_components(&rest[2..], is_separator);
+
+                        if components.is_empty() {
+                            return Err(WinFileInfoError::invalid_path(input, "device path has no device name"));
+                        }
+
+                        let name = components.remove(0);
+
+                        return Ok(WindowsPath { prefix: Some(PathPrefix::Device(name)), has_root: true, components });
+                    }
+                }
+
+                let (server, share, components) = parse_unc(input, rest, is_separator)?;
+
+                return Ok(WindowsPath { prefix: Some(PathPrefix::Unc { server, share }), has_root: true, components });
+            }
+        }
+
+        if let Some(letter) = drive_letter(input) {
+            return Ok(WindowsPath {
+                prefix: Some(PathPrefix::Disk(letter)),
+                has_root: input[2..].starts_with(is_separator),
+                components: split_components(&input[2..], is_separator)
+            });
+        }
+
+        Ok(WindowsPath { prefix: None, has_root: input.starts_with(is_separator), components: split_components(input, is_separator) })
+    }
+
+    pub fn prefix(&self) -> Option<&PathPrefix> {
+        self.prefix.as_ref()
+    }
+
+    // true when the path starts at the root of its prefix, "C:\foo" and "\foo" have it but "C:foo" doesn't.
+    pub fn has_root(&self) -> bool {
+        self.has_root
+    }
+
+    // rooted paths without a drive, like "\foo", are relative to the current drive so they are not absolute.
+    pub fn is_absolute(&self) -> bool {
+        self.prefix.is_some() && self.has_root
+    }
+
+    pub fn is_verbatim(&self) -> bool {
+        matches!(self.prefix, Some(PathPrefix::VerbatimDisk(_) | PathPrefix::VerbatimUnc { .. } | PathPrefix::Verbatim(_)))
+    }
+
+    pub fn components(&self) -> &[String] {
+        &self.components
+    }
+
+    pub fn file_name(&self) -> Option<&str> {
+        match self.components.last() {
+            Some(name) if name != "." && name != ".." => Some(name),
+            _ => None
+        }
+    }
+
+    // ".profile" has no extension and "archive.tar.gz" has "gz", same with "std::path".
+    pub fn extension(&self) -> Option<&str> {
+        match self.file_name()?.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => Some(extension),
+            _ => None
+        }
+    }
+
+    pub fn file_stem(&self) -> Option<&str> {
+        let file_name = self.file_name()?;
+
+        match file_name.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => Some(stem),
+            _ => Some(file_name)
+        }
+    }
+
+    // path without its last component, "None" if it has no component.
+    pub fn parent(&self) -> Option<WindowsPath> {
+        let mut parent = self.clone();
+
+        parent.components.pop()?;
+
+        Some(parent)
+    }
+
+    // "other" replaces the path if it has a root or a prefix of its own, like "Path::join" does.
+    pub fn join(&self, other: &str) -> Result<WindowsPath, WinFileInfoError> {
+        let other = WindowsPath::parse(other)?;
+
+        match (&other.prefix, other.has_root) {
+            (Some(_), _) => Ok(other),
+            (None, true) => Ok(WindowsPath { prefix: self.prefix.clone(), has_root: true, components: other.components }),
+            (None, false) => {
+                let mut joined = self.clone();
+                joined.components.extend(other.components);
+
+                Ok(joined)
+            }
+        }
+    }
+
+    // resolves "." and ".." components like windows does. ".." never goes above the root, relative paths keep
+    // the ones which they can't resolve. verbatim paths are not touched, since windows doesn't normalize them.
+    pub fn normalize(&self) -> WindowsPath {
+        if self.is_verbatim() {
+            return self.clone();
+        }
+
+        let mut components: Vec<String> = vec![];
+
+        for component in &self.components {
+            match component.as_str() {
+                "." => (),
+                ".." => match components.last() {
+                    Some(last) if last != ".." => {
+                        components.pop();
+                    },
+                    _ if self.has_root => (),
+                    _ => components.push(component.clone())
+                },
+                _ => components.push(component.clone())
+            }
+        }
+
+        WindowsPath { prefix: self.prefix.clone(), has_root: self.has_root, components }
+    }
+
+    // "\\?\C:\foo" or "\\?\UNC\server\share\foo", the path gets normalized first since windows won't do it for
+    // verbatim paths. "None" for the paths which are not absolute.
+    pub fn to_verbatim(&self) -> Option<WindowsPath> {
+        let path = self.normalize();
+
+        let prefix = match path.prefix.clone()? {
+            PathPrefix::Disk(letter) if path.has_root => PathPrefix::VerbatimDisk(letter),
+            PathPrefix::Device(name) => match drive_letter(&name) {
+                Some(letter) if name.len() == 2 => PathPrefix::VerbatimDisk(letter),
+                _ => PathPrefix::Verbatim(name)
+            },
+            PathPrefix::Unc { server, share } => PathPrefix::VerbatimUnc { server, share },
+            prefix @ (PathPrefix::VerbatimDisk(_) | PathPrefix::VerbatimUnc { .. } | PathPrefix::Verbatim(_)) => prefix,
+            PathPrefix::Disk(_) => return None
+        };
+
+        Some(WindowsPath { prefix: Some(prefix), ..path })
+    }
+
+    // "\\.\C:\foo", only for the paths on a disk.
+    pub fn to_device(&self) -> Option<WindowsPath> {
+        let path = self.normalize();
+
+        let name = match path.prefix.clone()? {
+            PathPrefix::Disk(letter) | PathPrefix::VerbatimDisk(letter) if path.has_root => format!("{}:", letter),
+            PathPrefix::Device(name) => name,
+            _ => return None
+        };
+
+        Some(WindowsPath { prefix: Some(PathPrefix::Device(name)), ..path })
+    }
+
+    // the usual form without "\\?\" or "\\.\", like "C:\foo" or "\\server\share\foo". "None" for the verbatim and
+    // device paths which have no such form, like volume guids and "COM1".
+    pub fn to_win32(&self) -> Option<WindowsPath> {
+        let prefix = match self.prefix.clone() {
+            Some(PathPrefix::VerbatimDisk(letter)) => Some(PathPrefix::Disk(letter)),
+            Some(PathPrefix::VerbatimUnc { server, share }) => Some(PathPrefix::Unc { server, share }),
+            Some(PathPrefix::Device(name)) => match drive_letter(&name) {
+                Some(letter) if name.len() == 2 => Some(PathPrefix::Disk(letter)),
+                _ => return None
+            },
+            Some(PathPrefix::Verbatim(_)) => return None,
+            prefix => prefix
+        };
+
+        Some(WindowsPath { prefix, has_root: self.has_root, components: self.components.clone() })
+    }
+}
+
+// same names for ntfs, which compares them upper-cased.
+fn same_name(left: &str, right: &str) -> bool {
+    left.encode_utf16().map(upcase).eq(right.encode_utf16().map(upcase))
+}
+
+fn same_prefix(left: Option<&PathPrefix>, right: Option<&PathPrefix>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(PathPrefix::Disk(left)), Some(PathPrefix::Disk(right))) => left.eq_ignore_ascii_case(right),
+        (Some(PathPrefix::Unc { server, share }), Some(PathPrefix::Unc { server: other_server, share: other_share })) => same_name(server, other_server) && same_name(share, other_share),
+        (Some(PathPrefix::Verbatim(left)), Some(PathPrefix::Verbatim(right))) | (Some(PathPrefix::Device(left)), Some(PathPrefix::Device(right))) => same_name(left, right),
+        _ => false
+    }
+}
+
+// "target" as a path relative to "base", both of them are normalized and the names are compared like ntfs does.
+// it's an error when they are not on the same drive or unc share, or when only one of them has a root:
+//
+// let relative = relative_to("C:\\Users\\necoo\\Desktop", "c:\\users\\NECOO\\Documents\\notes.txt").unwrap();
+//
+// assert_eq!(relative, "..\\Documents\\notes.txt");
+//
+// the same path gives ".". verbatim paths are compared in their usual form when they have one.
+pub fn relative_to(base: &str, target: &str) -> Result<String, WinFileInfoError> {
+    let usual = |path: WindowsPath| path.to_win32().unwrap_or(path).normalize();
+    let base_path = usual(WindowsPath::parse(base)?);
+    let target_path = usual(WindowsPath::parse(target)?);
+
+    if !same_prefix(base_path.prefix(), target_path.prefix()) {
+        return Err(WinFileInfoError::invalid_path(target, format!("it's not on the same drive or share as '{}'", base)));
+    }
+
+    if base_path.has_root() != target_path.has_root() {
+        return Err(WinFileInfoError::invalid_path(target, format!("only one of it and '{}' starts at the root", base)));
+    }
+
+    let common = base_path.components().iter().zip(target_path.components()).take_while(|(left, right)| same_name(left, right)).count();
+    let ups = &base_path.components()[common..];
+
+    // a relative base that still goes up can't be walked back.
+    if ups.iter().any(|component| component == "..") {
+        return Err(WinFileInfoError::invalid_path(base, "it goes above where it starts, the way back is not known"));
+    }
+
+    let components: Vec<&str> = ups.iter().map(|_| "..").chain(target_path.components()[common..].iter().map(|component| component.as_str())).collect();
+
+    match components.is_empty() {
+        true => Ok(".".to_string()),
+        false => Ok(components.join("\\"))
+    }
+}
+
+impl fmt::Display for WindowsPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.prefix {
+            Some(PathPrefix::Disk(letter)) => write!(f, "{}:", letter)?,
+            Some(PathPrefix::Unc { server, share }) => write!(f, "\\\\{}\\{}", server, share)?,
+            Some(PathPrefix::VerbatimDisk(letter)) => write!(f, "\\\\?\\{}:", letter)?,
+            Some(PathPrefix::VerbatimUnc { server, share }) => write!(f, "\\\\?\\UNC\\{}\\{}", server, share)?,
+            Some(PathPrefix::Verbatim(name)) => write!(f, "\\\\?\\{}", name)?,
+            Some(PathPrefix::Device(name)) => write!(f, "\\\\.\\{}", name)?,
+            None => ()
+        }
+
+        // unc, verbatim and device prefixes have no separator after them when there is nothing else.
+        let separator_after_prefix = match &self.prefix {
+            Some(PathPrefix::Disk(_)) | Some(PathPrefix::VerbatimDisk(_)) | None => self.has_root,
+            Some(_) => !self.components.is_empty()
+        };
+
+        if separator_after_prefix {
+            write!(f, "\\")?;
+        }
+
+        write!(f, "{}", self.components.join("\\"))
+    }
+}
+
+impl FromStr for WindowsPath {
+    type Err = WinFileInfoError;
+
+    fn from_str(input: &str) -> Result<WindowsPath, WinFileInfoError> {
+        WindowsPath::parse(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> WindowsPath {
+        WindowsPath::parse(input).unwrap()
+    }
+
+    #[test]
+    fn test_parse_forms(){
+        assert_eq!(parse("C:\\Users\\necoo").prefix(), Some(&PathPrefix::Disk('C')));
+        assert!(parse("C:\\Users").is_absolute());
+        assert!(!parse("C:Users").has_root() && !parse("C:Users").is_absolute());
+        assert!(parse("\\Users").has_root() && !parse("\\Users").is_absolute());
+        assert_eq!(parse("Users\\necoo").prefix(), None);
+        assert_eq!(parse("\\\\server\\share\\docs").prefix(), Some(&PathPrefix::Unc { server: "server".to_string(), share: "share".to_string() }));
+        assert_eq!(parse("\\\\?\\C:\\Users").prefix(), Some(&PathPrefix::VerbatimDisk('C')));
+        assert_eq!(parse("\\\\?\\UNC\\server\\share\\docs").prefix(), Some(&PathPrefix::VerbatimUnc { server: "server".to_string(), share: "share".to_string() }));
+        assert_eq!(parse("\\\\?\\Volume{26a21bda-a627-11d7-9931-806e6f6e6963}\\Windows").prefix(), Some(&PathPrefix::Verbatim("Volume{26a21bda-a627-11d7-9931-806e6f6e6963}".to_string())));
+        assert_eq!(parse("\\\\.\\PhysicalDrive0").prefix(), Some(&PathPrefix::Device("PhysicalDrive0".to_string())));
+        assert_eq!(parse("//server/share/docs").components(), ["docs"]);
+    }
+
+    #[test]
+    fn test_display_round_trip(){
+        let samples = ["C:\\Users\\necoo", "C:\\", "C:", "C:notes.txt", "\\Windows", "notes\\a.txt", "\\\\server\\share", "\\\\server\\share\\docs\\a.txt",
+                       "\\\\?\\C:\\Users", "\\\\?\\UNC\\server\\share\\docs", "\\\\?\\Volume{26a21bda}\\Windows", "\\\\.\\COM1", "\\\\.\\C:\\Users", ""];
+
+        for sample in samples {
+            assert_eq!(parse(sample).to_string(), sample);
+        }
+
+        assert_eq!(parse("C:/Users//necoo/").to_string(), "C:\\Users\\necoo");
+    }
+
+    #[test]
+    fn test_unc_paths_are_not_mangled(){
+        let path = parse("\\\\server\\share\\docs");
+
+        assert_eq!(path.join("a.txt").unwrap().to_string(), "\\\\server\\share\\docs\\a.txt");
+        assert_eq!(path.to_verbatim().unwrap().to_string(), "\\\\?\\UNC\\server\\share\\docs");
+        assert_eq!(parse("\\\\?\\UNC\\server\\share\\docs").to_win32().unwrap(), path);
+    }
+
+    #[test]
+    fn test_normalize(){
+        assert_eq!(parse("C:/Users\\necoo/./Desktop\\..\\notes.txt").normalize().to_string(), "C:\\Users\\necoo\\notes.txt");
+        assert_eq!(parse("C:\\..\\..\\Windows").normalize().to_string(), "C:\\Windows");
+        assert_eq!(parse("..\\a\\..\\..\\b").normalize().to_string(), "..\\..\\b");
+        assert_eq!(parse("\\\\server\\share\\..\\docs").normalize().to_string(), "\\\\server\\share\\docs");
+        // verbatim paths are passed as they are:
+        assert_eq!(parse("\\\\?\\C:\\a\\..\\b").normalize().to_string(), "\\\\?\\C:\\a\\..\\b");
+        assert_eq!(parse("\\\\?\\C:/a").prefix(), Some(&PathPrefix::Verbatim("C:/a".to_string())));
+    }
+
+    #[test]
+    fn test_file_name_and_extension(){
+        let path = parse("C:\\Users\\necoo\\archive.tar.gz");
+
+        assert_eq!(path.file_name(), Some("archive.tar.gz"));
+        assert_eq!(path.extension(), Some("gz"));
+        assert_eq!(path.file_stem(), Some("archive.tar"));
+        assert_eq!(parse(".profile").extension(), None);
+        assert_eq!(parse(".profile").file_stem(), Some(".profile"));
+        assert_eq!(parse("C:\\").file_name(), None);
+        assert_eq!(parse("C:\\Users\\..").file_name(), None);
+        assert_eq!(path.parent().unwrap().to_string(), "C:\\Users\\necoo");
+    }
+
+    #[test]
+    fn test_conversions(){
+        let path = parse("C:\\Users\\necoo\\..\\Public");
+
+        assert_eq!(path.to_verbatim().unwrap().to_string(), "\\\\?\\C:\\Users\\Public");
+        assert_eq!(path.to_device().unwrap().to_string(), "\\\\.\\C:\\Users\\Public");
+        assert_eq!(parse("\\\\.\\C:\\Users").to_win32().unwrap().to_string(), "C:\\Users");
+        assert_eq!(parse("\\\\.\\C:\\Users").to_verbatim().unwrap().to_string(), "\\\\?\\C:\\Users");
+        assert_eq!(parse("C:Users").to_verbatim(), None);
+        assert_eq!(parse("\\\\.\\COM1").to_win32(), None);
+        assert_eq!(parse("C:\\Users").join("\\Windows").unwrap().to_string(), "C:\\Windows");
+        assert_eq!(parse("C:\\Users").join("D:\\data").unwrap().to_string(), "D:\\data");
+    }
+
+    #[test]
+    fn test_invalid_paths(){
+        assert!(matches!(WindowsPath::parse("\\\\server"), Err(WinFileInfoError::InvalidPath { .. })));
+        assert!(WindowsPath::parse("\\\\").is_err());
+        assert!(WindowsPath::parse("\\\\?\\UNC\\server").is_err());
+        assert!(WindowsPath::parse("\\\\?\\").is_err());
+        assert!(WindowsPath::parse("\\\\.\\").is_err());
+    }
+
+    #[test]
+    fn test_relative_to(){
+        assert_eq!(relative_to("C:\\Users\\necoo\\Desktop", "c:\\users\\NECOO\\Documents\\notes.txt").unwrap(), "..\\Documents\\notes.txt");
+        assert_eq!(relative_to("C:\\Users\\necoo", "C:/Users/necoo/Desktop/a.txt").unwrap(), "Desktop\\a.txt");
+        assert_eq!(relative_to("C:\\Users\\necoo\\", "c:\\USERS\\necoo").unwrap(), ".");
+        assert_eq!(relative_to("C:\\a\\b\\c", "C:\\").unwrap(), "..\\..\\..");
+        assert_eq!(relative_to("C:\\Users\\Çalışma", "C:\\Users\\çALıŞMA\\x").unwrap(), "x");
+        assert_eq!(relative_to("\\\\?\\C:\\Users", "C:\\Users\\necoo\\..\\Public").unwrap(), "Public");
+        assert_eq!(relative_to("\\\\Server\\Share\\docs", "\\\\server\\share\\images\\a.png").unwrap(), "..\\images\\a.png");
+        assert_eq!(relative_to("projects\\a", "projects\\b").unwrap(), "..\\b");
+    }
+
+    #[test]
+    fn test_relative_to_errors(){
+        for (base, target) in [
+            ("C:\\Users", "D:\\Users"),
+            ("\\\\server\\share", "\\\\server\\other"),
+            ("C:\\Users", "\\\\server\\share\\Users"),
+            ("C:\\Users", "C:Users"),
+            ("..\\a", "b"),
+            ("\\\\server", "C:\\")
+        ] {
+            assert!(matches!(relative_to(base, target), Err(WinFileInfoError::InvalidPath { .. })), "{} {}", base, target);
+        }
+    }
+}
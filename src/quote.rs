@@ -0,0 +1,132 @@
+// quoting of the values that we put into powershell commands. every path and filter the crate sends goes through
+// here, so a name like "x'; Remove-Item C:\ -Recurse; '" stays a name instead of becoming a command.
+//
+// sample use:
+//
+// let command = format!("Get-Item {}", literal_path("C:\\Users\\necoo\\it's mine.txt"));
+//
+// assert_eq!(command, "Get-Item -LiteralPath 'C:\\Users\\necoo\\it''s mine.txt'");
+
+// powershell treats the unicode single quotation marks like "'" too, any of them ends a single-quoted string.
+const SINGLE_QUOTES: [char; 5] = ['\'', '\u{2018}', '\u{2019}', '\u{201A}', '\u{201B}'];
+
+pub fn is_single_quote(character: char) -> bool {
+    SINGLE_QUOTES.contains(&character)
+}
+
+// single-quoted powershell literal of given value. nothing is expanded inside of single quotes, so the only thing
+// to escape is the quotes themselves, which is done by doubling them.
+pub fn single_quoted(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('\'');
+
+    for character in value.chars() {
+        if is_single_quote(character) {
+            quoted.push(character);
+        }
+
+        quoted.push(character);
+    }
+
+    quoted.push('\'');
+
+    quoted
+}
+
+// "-LiteralPath" doesn't expand the wildcards, so names with "[", "]", "*" or "?" are found as they are.
+pub fn literal_path(path: &str) -> String {
+    format!("-LiteralPath {}", single_quoted(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reads a single-quoted string like the tokenizer of powershell does, gives the value and what comes after it.
+    fn read_single_quoted(input: &str) -> Option<(String, &str)> {
+        let mut chars = input.char_indices().peekable();
+        let mut value = String::new();
+
+        match chars.next() {
+            Some((_, character)) if is_single_quote(character) => (),
+            _ => return None
+        }
+
+        while let Some((index, character)) = chars.next() {
+            if is_single_quote(character) {
+                match chars.peek() {
+                    Some((_, next)) if is_single_quote(*next) => {
+                        value.push(character);
+                        chars.next();
+                    },
+                    _ => return Some((value, &input[index + character.len_utf8()..]))
+                }
+            } else {
+                value.push(character);
+            }
+        }
+
+        None
+    }
+
+    const HOSTILE_NAMES: [&str; 18] = [
+        "it's mine.txt",
+        "x'; Remove-Item C:\\ -Recurse -Force; '",
+        "'",
+        "''",
+        "'''",
+        "\u{2018}smart\u{2019} quotes.txt",
+        "x\u{2019}; Stop-Computer; \u{2018}",
+        "\u{201A}\u{201B}\u{2019}\u{2018}'",
+        "$(Remove-Item C:\\)",
+        "$env:USERPROFILE",
+        "`$backtick`'",
+        "a\"b\"c",
+        "[brackets] and *wildcards?.txt",
+        "name with\nnew line",
+        "; & | > < @ # % ^",
+        "ends with a quote'",
+        "report : final 日本語 🙂.txt",
+        ""
+    ];
+
+    #[test]
+    fn test_single_quoted(){
+        assert_eq!(single_quoted("C:\\Users"), "'C:\\Users'");
+        assert_eq!(single_quoted("it's"), "'it''s'");
+        assert_eq!(single_quoted("\u{2019}"), "'\u{2019}\u{2019}'");
+        assert_eq!(literal_path("C:\\a[1].txt"), "-LiteralPath 'C:\\a[1].txt'");
+    }
+
+    #[test]
+    fn test_hostile_names_stay_literal(){
+        for name in HOSTILE_NAMES {
+            let command = format!("Get-Item {} -Force", literal_path(name));
+            let (value, rest) = read_single_quoted(command.strip_prefix("Get-Item -LiteralPath ").unwrap()).unwrap();
+
+            assert_eq!(value, name);
+            assert_eq!(rest, " -Force");
+        }
+    }
+
+    #[test]
+    fn test_fuzzed_names_stay_literal(){
+        let fragments = ["'", "\u{2018}", "\u{2019}", "\u{201A}", "\u{201B}", "\"", "`", "$", ";", " ", "a", "\\", "(", ")", "日", "\n", "-"];
+        let mut seed: u64 = 0x5EED;
+
+        for _ in 0..2000 {
+            let mut name = String::new();
+
+            for _ in 0..(seed % 12) {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                name.push_str(fragments[(seed >> 33) as usize % fragments.len()]);
+            }
+
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+
+            let quoted = single_quoted(&name);
+
+            assert_eq!(read_single_quoted(&quoted), Some((name.clone(), "")), "{:?}", name);
+        }
+    }
+}
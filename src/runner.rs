@@ -0,0 +1,136 @@
+use std::{collections::VecDeque, io::{Error, ErrorKind}, process::Command, sync::Mutex};
+
+// captured result of a single shell invocation. it's our own type instead of "std::process::Output" because
+// "ExitStatus" can't be created by hand, which makes replaying recorded output impossible.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub success: bool
+}
+
+impl CommandOutput {
+    pub fn new(stdout: impl Into<Vec<u8>>, stderr: impl Into<Vec<u8>>, success: bool) -> CommandOutput {
+        CommandOutput { stdout: stdout.into(), stderr: stderr.into(), success }
+    }
+}
+
+// everything that runs a powershell command for that crate goes through that trait. the command is always a
+// complete powershell script, which the runner decides how to execute.
+pub trait CommandRunner {
+    fn run(&self, command: &str) -> Result<CommandOutput, Error>;
+}
+
+impl<R: CommandRunner + ?Sized> CommandRunner for &R {
+    fn run(&self, command: &str) -> Result<CommandOutput, Error> {
+        (**self).run(command)
+    }
+}
+
+// default runner, spawns "powershell" for every command, which is what that crate always did.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PowerShellRunner;
+
+impl CommandRunner for PowerShellRunner {
+    fn run(&self, command: &str) -> Result<CommandOutput, Error> {
+        let _turn_terminal_into_utf8 = Command::new("cmd")
+                                                    .args(["/C", "chcp 65001"])
+                                                    .output()?;
+
+        let output = Command::new("powershell")
+                                    .args(["-Command", command])
+                                    .output()?;
+
+        Ok(CommandOutput {
+            stdout: output.stdout,
+            stderr: output.stderr,
+            success: output.status.success()
+        })
+    }
+}
+
+// runner that replays captured powershell transcripts in the order they're pushed, so the whole parsing
+// pipeline can be tested on any platform. every command it receives is recorded for later assertions.
+//
+// sample use:
+//
+// let runner = FixtureRunner::new().with_stdout(include_str!("../tests/fixtures/folder_listing.json"));
+// let entities = other_folder_info_with(&runner, "C:\\Users\\necoo\\Desktop").unwrap();
+#[derive(Debug, Default)]
+pub struct FixtureRunner {
+    outputs: Mutex<VecDeque<CommandOutput>>,
+    commands: Mutex<Vec<String>>
+}
+
+impl FixtureRunner {
+    pub fn new() -> FixtureRunner {
+        FixtureRunner::default()
+    }
+
+    pub fn with_output(self, output: CommandOutput) -> FixtureRunner {
+        self.outputs.lock().unwrap().push_back(output);
+
+        self
+    }
+
+    pub fn with_stdout(self, stdout: impl Into<Vec<u8>>) -> FixtureRunner {
+        self.with_output(CommandOutput::new(stdout, vec![], true))
+    }
+
+    pub fn with_stderr(self, stderr: impl Into<Vec<u8>>) -> FixtureRunner {
+        self.with_output(CommandOutput::new(vec![], stderr, false))
+    }
+
+    pub fn with_stdout_file(self, path: &str) -> Result<FixtureRunner, Error> {
+        let stdout = std::fs::read(path)?;
+
+        Ok(self.with_stdout(stdout))
+    }
+
+    // commands that runner received until now, in order.
+    pub fn commands(&self) -> Vec<String> {
+        self.commands.lock().unwrap().clone()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.outputs.lock().unwrap().len()
+    }
+}
+
+impl CommandRunner for FixtureRunner {
+    fn run(&self, command: &str) -> Result<CommandOutput, Error> {
+        self.commands.lock().unwrap().push(command.to_string());
+
+        match self.outputs.lock().unwrap().pop_front() {
+            Some(output) => Ok(output),
+            None => Err(Error::new(ErrorKind::NotFound, format!("there is no recorded output left for that command: {}", command)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_runner_replays_in_order(){
+        let runner = FixtureRunner::new().with_stdout("first").with_stderr("second");
+
+        let first = runner.run("Get-Item -Path 'a'").unwrap();
+        let second = runner.run("Get-Item -Path 'b'").unwrap();
+
+        assert_eq!(first.stdout, b"first");
+        assert!(first.success);
+        assert_eq!(second.stderr, b"second");
+        assert!(!second.success);
+        assert_eq!(runner.commands(), vec!["Get-Item -Path 'a'", "Get-Item -Path 'b'"]);
+        assert_eq!(runner.remaining(), 0);
+    }
+
+    #[test]
+    fn test_fixture_runner_runs_out(){
+        let runner = FixtureRunner::new();
+
+        assert_eq!(runner.run("Get-Item").unwrap_err().kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,682 @@
+use std::{fmt, ops::{BitOr, BitOrAssign}, str::FromStr};
+
+use crate::{acl::{AccessControlEntry, AceFlags, AceType, EntityAcl, FileSystemRights}, error::WinFileInfoError};
+
+// the sid aliases of sddl which mean the same sid on every machine. they are turned into their sids while
+// parsing and back into the aliases while writing, like windows does.
+const SID_ALIASES: [(&str, &str); 48] = [
+    ("AA", "S-1-5-32-579"),
+    ("AC", "S-1-15-2-1"),
+    ("AN", "S-1-5-7"),
+    ("AO", "S-1-5-32-548"),
+    ("AS", "S-1-18-1"),
+    ("AU", "S-1-5-11"),
+    ("BA", "S-1-5-32-544"),
+    ("BG", "S-1-5-32-546"),
+    ("BO", "S-1-5-32-551"),
+    ("BU", "S-1-5-32-545"),
+    ("CD", "S-1-5-32-574"),
+    ("CG", "S-1-3-1"),
+    ("CO", "S-1-3-0"),
+    ("CY", "S-1-5-32-569"),
+    ("ED", "S-1-5-9"),
+    ("ER", "S-1-5-32-573"),
+    ("ES", "S-1-5-32-576"),
+    ("HA", "S-1-5-32-578"),
+    ("HI", "S-1-16-12288"),
+    ("IS", "S-1-5-32-568"),
+    ("IU", "S-1-5-4"),
+    ("LS", "S-1-5-19"),
+    ("LU", "S-1-5-32-559"),
+    ("LW", "S-1-16-4096"),
+    ("ME", "S-1-16-8192"),
+    ("MP", "S-1-16-8448"),
+    ("MU", "S-1-5-32-558"),
+    ("NO", "S-1-5-32-556"),
+    ("NS", "S-1-5-20"),
+    ("NU", "S-1-5-2"),
+    ("OW", "S-1-3-4"),
+    ("PO", "S-1-5-32-550"),
+    ("PS", "S-1-5-10"),
+    ("PU", "S-1-5-32-547"),
+    ("RA", "S-1-5-32-575"),
+    ("RC", "S-1-5-12"),
+    ("RD", "S-1-5-32-555"),
+    ("RE", "S-1-5-32-552"),
+    ("RM", "S-1-5-32-580"),
+    ("RU", "S-1-5-32-554"),
+    ("SI", "S-1-16-16384"),
+    ("SO", "S-1-5-32-549"),
+    ("SS", "S-1-18-2"),
+    ("SU", "S-1-5-6"),
+    ("SY", "S-1-5-18"),
+    ("UD", "S-1-5-84-0-0-0-0-0"),
+    ("WD", "S-1-1-0"),
+    ("WR", "S-1-5-33")
+];
+
+// the aliases which are relative to a domain, like "DA" for its "Domain Admins". we can't know the domain
+// without asking windows, so they are kept as the aliases. the numbers are their rids.
+pub const DOMAIN_SID_ALIASES: [(&str, u32); 17] = [
+    ("RO", 498),
+    ("LA", 500),
+    ("LG", 501),
+    ("DA", 512),
+    ("DU", 513),
+    ("DG", 514),
+    ("DC", 515),
+    ("DD", 516),
+    ("CA", 517),
+    ("SA", 518),
+    ("EA", 519),
+    ("PA", 520),
+    ("CN", 522),
+    ("AP", 525),
+    ("KA", 526),
+    ("EK", 527),
+    ("RS", 553)
+];
+
+const ACE_TYPES: [(&str, AceType); 15] = [
+    ("A", AceType::Allow),
+    ("D", AceType::Deny),
+    ("AU", AceType::Audit),
+    ("AL", AceType::Alarm),
+    ("OA", AceType::AllowObject),
+    ("OD", AceType::DenyObject),
+    ("OU", AceType::AuditObject),
+    ("OL", AceType::AlarmObject),
+    ("XA", AceType::AllowCallback),
+    ("XD", AceType::DenyCallback),
+    ("XU", AceType::AuditCallback),
+    ("ZA", AceType::AllowCallbackObject),
+    ("ML", AceType::MandatoryLabel),
+    ("RA", AceType::ResourceAttribute),
+    ("SP", AceType::ScopedPolicyId)
+];
+
+const ACE_FLAGS: [(&str, AceFlags); 7] = [
+    ("OI", AceFlags::OBJECT_INHERIT),
+    ("CI", AceFlags::CONTAINER_INHERIT),
+    ("NP", AceFlags::NO_PROPAGATE_INHERIT),
+    ("IO", AceFlags::INHERIT_ONLY),
+    ("ID", AceFlags::INHERITED),
+    ("SA", AceFlags::SUCCESSFUL_ACCESS),
+    ("FA", AceFlags::FAILED_ACCESS)
+];
+
+// the rights which are a whole mask on their own, a mask is written with them only when it's exactly one of
+// them. "KX" is the same mask with "KR", so it's never written.
+const COMPOSITE_RIGHTS: [(&str, u32); 8] = [
+    ("FA", 0x1F01FF),
+    ("FR", 0x120089),
+    ("FW", 0x120116),
+    ("FX", 0x1200A0),
+    ("KA", 0xF003F),
+    ("KR", 0x20019),
+    ("KW", 0x20006),
+    ("KX", 0x20019)
+];
+
+// the single rights, in the order of their bits like windows writes them.
+const RIGHTS: [(&str, u32); 17] = [
+    ("CC", 0x1),
+    ("DC", 0x2),
+    ("LC", 0x4),
+    ("SW", 0x8),
+    ("RP", 0x10),
+    ("WP", 0x20),
+    ("DT", 0x40),
+    ("LO", 0x80),
+    ("CR", 0x100),
+    ("SD", 0x10000),
+    ("RC", 0x20000),
+    ("WD", 0x40000),
+    ("WO", 0x80000),
+    ("GA", 0x10000000),
+    ("GX", 0x20000000),
+    ("GW", 0x40000000),
+    ("GR", 0x80000000)
+];
+
+// the policy of the mandatory label aces, they are written only for them.
+const LABEL_RIGHTS: [(&str, u32); 3] = [
+    ("NW", 0x1),
+    ("NR", 0x2),
+    ("NX", 0x4)
+];
+
+const NULL_ACL: &str = "NO_ACCESS_CONTROL";
+
+// the "SECURITY_DESCRIPTOR_CONTROL" bits which sddl can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DescriptorControl(u16);
+
+impl DescriptorControl {
+    pub const DACL_PRESENT: DescriptorControl = DescriptorControl(0x4);
+    pub const SACL_PRESENT: DescriptorControl = DescriptorControl(0x10);
+    pub const DACL_AUTO_INHERIT_REQUIRED: DescriptorControl = DescriptorControl(0x100);
+    pub const SACL_AUTO_INHERIT_REQUIRED: DescriptorControl = DescriptorControl(0x200);
+    pub const DACL_AUTO_INHERITED: DescriptorControl = DescriptorControl(0x400);
+    pub const SACL_AUTO_INHERITED: DescriptorControl = DescriptorControl(0x800);
+    pub const DACL_PROTECTED: DescriptorControl = DescriptorControl(0x1000);
+    pub const SACL_PROTECTED: DescriptorControl = DescriptorControl(0x2000);
+
+    pub const fn empty() -> DescriptorControl {
+        DescriptorControl(0)
+    }
+
+    pub const fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub const fn from_bits_retain(bits: u16) -> DescriptorControl {
+        DescriptorControl(bits)
+    }
+
+    pub const fn contains(&self, other: DescriptorControl) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: DescriptorControl) {
+        self.0 |= other.0
+    }
+
+    pub fn remove(&mut self, other: DescriptorControl) {
+        self.0 &= !other.0
+    }
+
+    pub fn set(&mut self, other: DescriptorControl, value: bool) {
+        match value {
+            true => self.insert(other),
+            false => self.remove(other)
+        }
+    }
+}
+
+impl BitOr for DescriptorControl {
+    type Output = DescriptorControl;
+
+    fn bitor(self, other: DescriptorControl) -> DescriptorControl {
+        DescriptorControl(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for DescriptorControl {
+    fn bitor_assign(&mut self, other: DescriptorControl) {
+        self.0 |= other.0
+    }
+}
+
+// a security descriptor in its sddl form, like "O:BAG:SYD:PAI(A;OICI;FA;;;SY)(A;;0x1200a9;;;BU)". the owner,
+// the group and the trustees are sid strings, or the aliases of "DOMAIN_SID_ALIASES" which we can't resolve:
+//
+// let descriptor = SecurityDescriptor::parse("O:BAG:SYD:PAI(A;OICI;FA;;;SY)").unwrap();
+//
+// assert_eq!(descriptor.owner.as_deref(), Some("S-1-5-32-544"));
+// assert_eq!(descriptor.to_string(), "O:BAG:SYD:PAI(A;OICI;FA;;;SY)");
+//
+// a dacl that is present but "None" is a null dacl, "D:NO_ACCESS_CONTROL", which gives everyone every access.
+// an empty one gives nobody any access.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SecurityDescriptor {
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    pub dacl: Option<Vec<AccessControlEntry>>,
+    pub sacl: Option<Vec<AccessControlEntry>>,
+    pub control: DescriptorControl
+}
+
+impl SecurityDescriptor {
+    pub fn parse(sddl: &str) -> Result<SecurityDescriptor, WinFileInfoError> {
+        let characters: Vec<char> = sddl.chars().collect();
+        let error = |reason: String| WinFileInfoError::unparseable(sddl, reason);
+        let mut descriptor = SecurityDescriptor::default();
+        let mut seen = vec![];
+        let mut position = 0;
+
+        loop {
+            while characters.get(position).is_some_and(|character| character.is_whitespace()) {
+                position += 1;
+            }
+
+            let (tag, colon) = match (characters.get(position), characters.get(position + 1)) {
+                (None, _) => break,
+                (Some(tag), Some(':')) if "OGDS".contains(*tag) => (*tag, position + 1),
+                _ => return Err(error(format!("expected 'O:', 'G:', 'D:' or 'S:' at {}", position)))
+            };
+
+            if seen.contains(&tag) {
+                return Err(error(format!("'{}:' is given twice", tag)));
+            }
+
+            seen.push(tag);
+            position = colon + 1;
+
+            match tag {
+                'O' | 'G' => {
+                    let end = (position..characters.len()).find(|index| characters.get(index + 1) == Some(&':') && "OGDS".contains(characters[*index])).unwrap_or(characters.len());
+                    let trustee = parse_trustee(&characters[position..end].iter().collect::<String>()).map_err(error)?;
+
+                    match tag {
+                        'O' => descriptor.owner = Some(trustee),
+                        _ => descriptor.group = Some(trustee)
+                    }
+
+                    position = end;
+                },
+                _ => {
+                    let (entries, flags, next) = parse_acl(&characters, position, tag == 'S').map_err(error)?;
+
+                    match tag {
+                        'D' => {
+                            descriptor.dacl = entries;
+                            descriptor.control |= flags | DescriptorControl::DACL_PRESENT;
+                        },
+                        _ => {
+                            descriptor.sacl = entries;
+                            descriptor.control |= flags | DescriptorControl::SACL_PRESENT;
+                        }
+                    }
+
+                    position = next;
+                }
+            }
+        }
+
+        Ok(descriptor)
+    }
+
+    // the typed acl of "entity_acl()" for that descriptor.
+    pub fn to_entity_acl(&self) -> EntityAcl {
+        EntityAcl {
+            owner: self.owner.clone().unwrap_or_default(),
+            group: self.group.clone().unwrap_or_default(),
+            access: self.dacl.clone().unwrap_or_default(),
+            audit: self.sacl.clone().unwrap_or_default(),
+            access_protected: self.control.contains(DescriptorControl::DACL_PROTECTED),
+            audit_protected: self.control.contains(DescriptorControl::SACL_PROTECTED),
+            null_access: self.dacl.is_none() && self.control.contains(DescriptorControl::DACL_PRESENT),
+            sddl: self.to_string()
+        }
+    }
+}
+
+// reads the flags and the aces of a "D:" or "S:" part, up to the next part. "None" for the aces means a null acl.
+fn parse_acl(characters: &[char], start: usize, system: bool) -> Result<(Option<Vec<AccessControlEntry>>, DescriptorControl, usize), String> {
+    let (protected, auto_inherit_required, auto_inherited) = match system {
+        true => (DescriptorControl::SACL_PROTECTED, DescriptorControl::SACL_AUTO_INHERIT_REQUIRED, DescriptorControl::SACL_AUTO_INHERITED),
+        false => (DescriptorControl::DACL_PROTECTED, DescriptorControl::DACL_AUTO_INHERIT_REQUIRED, DescriptorControl::DACL_AUTO_INHERITED)
+    };
+
+    let starts_with = |position: usize, text: &str| characters[position.min(characters.len())..].iter().take(text.len()).copied().eq(text.chars());
+    let mut flags = DescriptorControl::empty();
+    let mut null = false;
+    let mut position = start;
+
+    loop {
+        match () {
+            _ if starts_with(position, NULL_ACL) => {
+                null = true;
+                position += NULL_ACL.len();
+            },
+            _ if starts_with(position, "AR") => {
+                flags.insert(auto_inherit_required);
+                position += 2;
+            },
+            _ if starts_with(position, "AI") => {
+                flags.insert(auto_inherited);
+                position += 2;
+            },
+            _ if starts_with(position, "P") => {
+                flags.insert(protected);
+                position += 1;
+            },
+            _ => break
+        }
+    }
+
+    let mut entries = vec![];
+
+    loop {
+        while characters.get(position).is_some_and(|character| character.is_whitespace()) {
+            position += 1;
+        }
+
+        if characters.get(position) != Some(&'(') {
+            break;
+        }
+
+        let end = closing_parenthesis(characters, position).ok_or(format!("ace at {} has no closing ')'", position))?;
+
+        entries.push(parse_ace(&characters[position + 1..end].iter().collect::<String>())?);
+        position = end + 1;
+    }
+
+    match (null, entries.is_empty()) {
+        (true, false) => Err(format!("'{}' acl has aces", NULL_ACL)),
+        (true, true) => Ok((None, flags, position)),
+        (false, _) => Ok((Some(entries), flags, position))
+    }
+}
+
+// the ")" which closes the "(" at "start", the conditions of the callback aces have their own parentheses and
+// strings which may have anything in them.
+fn closing_parenthesis(characters: &[char], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut quoted = false;
+
+    for (index, character) in characters.iter().enumerate().skip(start) {
+        match (quoted, character) {
+            (true, '"') => quoted = false,
+            (true, _) => (),
+            (false, '"') => quoted = true,
+            (false, '(') => depth += 1,
+            (false, ')') => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some(index);
+                }
+            },
+            _ => ()
+        }
+    }
+
+    None
+}
+
+// a single ace without its parentheses, "A;OICI;FA;;;SY". the seventh field, when there is one, is the condition
+// or the attribute and can have ";" in it.
+pub(crate) fn parse_ace(ace: &str) -> Result<AccessControlEntry, String> {
+    let fields: Vec<&str> = ace.splitn(7, ';').map(|field| field.trim()).collect();
+
+    if fields.len() < 6 {
+        return Err(format!("ace '{}' has {} fields instead of 6", ace, fields.len()));
+    }
+
+    let ace_type = ACE_TYPES.iter().find(|(name, _)| name.eq_ignore_ascii_case(fields[0])).map(|(_, ace_type)| *ace_type).ok_or(format!("'{}' is not an ace type", fields[0]))?;
+    let flags = parse_tokens(fields[1], &ACE_FLAGS.map(|(name, flag)| (name, flag.bits() as u32))).ok_or(format!("'{}' are not ace flags", fields[1]))?;
+
+    Ok(AccessControlEntry {
+        ace_type,
+        trustee: parse_trustee(fields[5])?,
+        rights: parse_rights(fields[2])?,
+        flags: AceFlags::from_bits_retain(flags as u8),
+        object_type: fields[3].to_ascii_lowercase(),
+        inherited_object_type: fields[4].to_ascii_lowercase(),
+        application_data: fields.get(6).map(|data| data.to_string()).unwrap_or_default()
+    })
+}
+
+// "FA", "CCDCLCSWRPWPDTLOCRSDRCWDWO", "0x1200a9" or a decimal mask.
+pub(crate) fn parse_rights(rights: &str) -> Result<FileSystemRights, String> {
+    let error = || format!("'{}' are not access rights", rights);
+
+    let bits = match rights.strip_prefix("0x").or_else(|| rights.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).map_err(|_| error())?,
+        None if rights.starts_with(|character: char| character.is_ascii_digit()) => rights.parse::<u32>().map_err(|_| error())?,
+        None => {
+            let aliases: Vec<(&str, u32)> = COMPOSITE_RIGHTS.iter().chain(RIGHTS.iter()).chain(LABEL_RIGHTS.iter()).copied().collect();
+
+            parse_tokens(rights, &aliases).ok_or_else(error)?
+        }
+    };
+
+    Ok(FileSystemRights::from_bits_retain(bits))
+}
+
+// two letter tokens one after another, like "OICIID".
+fn parse_tokens(text: &str, tokens: &[(&str, u32)]) -> Option<u32> {
+    let characters: Vec<char> = text.chars().collect();
+    let mut bits = 0;
+
+    for token in characters.chunks(2) {
+        let token: String = token.iter().collect();
+
+        bits |= tokens.iter().find(|(name, _)| name.eq_ignore_ascii_case(&token))?.1;
+    }
+
+    Some(bits)
+}
+
+// sid strings are kept as they are, the aliases become their sids when they have one.
+pub(crate) fn parse_trustee(trustee: &str) -> Result<String, String> {
+    let trustee = trustee.trim();
+
+    if let Some(rest) = trustee.strip_prefix("S-").or_else(|| trustee.strip_prefix("s-")) {
+        let number = |part: &str| match part.strip_prefix("0x").or_else(|| part.strip_prefix("0X")) {
+            Some(hex) => !hex.is_empty() && hex.chars().all(|character| character.is_ascii_hexdigit()),
+            None => !part.is_empty() && part.chars().all(|character| character.is_ascii_digit())
+        };
+
+        // the identifier authority can be written in hex, like "S-1-0x100000000000-1".
+        return match rest.split('-').count() >= 2 && rest.split('-').all(number) {
+            true => Ok(format!("S-{}", rest)),
+            false => Err(format!("'{}' is not a sid", trustee))
+        };
+    }
+
+    let alias = trustee.to_ascii_uppercase();
+
+    if let Some((_, sid)) = SID_ALIASES.iter().find(|(name, _)| *name == alias) {
+        return Ok(sid.to_string());
+    }
+
+    match DOMAIN_SID_ALIASES.iter().any(|(name, _)| *name == alias) {
+        true => Ok(alias),
+        false => Err(format!("'{}' is not a sid or a sid alias", trustee))
+    }
+}
+
+// the alias of a sid string, if sddl has one for it.
+pub fn sid_alias(sid: &str) -> Option<&'static str> {
+    SID_ALIASES.iter().find(|(_, alias_sid)| alias_sid.eq_ignore_ascii_case(sid)).map(|(alias, _)| *alias)
+}
+
+// the sid string of an alias, "None" for the domain aliases.
+pub fn alias_sid(alias: &str) -> Option<&'static str> {
+    SID_ALIASES.iter().find(|(name, _)| name.eq_ignore_ascii_case(alias)).map(|(_, sid)| *sid)
+}
+
+// a mask like windows writes it: one of the composite aliases if it's exactly one of them, the single aliases
+// if they cover every bit, and the hex number otherwise. that's why the file masks with "Synchronize" in them
+// are mostly hex, "Synchronize" has no alias.
+pub fn rights_to_sddl(rights: FileSystemRights, ace_type: AceType) -> String {
+    let bits = rights.bits();
+
+    if bits == 0 {
+        return "".to_string();
+    }
+
+    let singles: &[(&str, u32)] = match ace_type {
+        AceType::MandatoryLabel => &LABEL_RIGHTS,
+        _ => {
+            if let Some((alias, _)) = COMPOSITE_RIGHTS.iter().find(|(_, mask)| *mask == bits) {
+                return alias.to_string();
+            }
+
+            &RIGHTS
+        }
+    };
+
+    let covered = singles.iter().fold(0, |covered, (_, mask)| covered | (bits & mask));
+
+    match covered == bits {
+        true => singles.iter().filter(|(_, mask)| bits & mask != 0).map(|(alias, _)| *alias).collect(),
+        false => format!("{:#x}", bits)
+    }
+}
+
+// an ace in its sddl form with its parentheses.
+pub fn ace_to_sddl(ace: &AccessControlEntry) -> String {
+    let ace_type = ACE_TYPES.iter().find(|(_, ace_type)| *ace_type == ace.ace_type).map(|(name, _)| *name).unwrap_or("A");
+    let flags: String = ACE_FLAGS.iter().filter(|(_, flag)| ace.flags.contains(*flag)).map(|(name, _)| *name).collect();
+    let mut fields = vec![ace_type.to_string(), flags, rights_to_sddl(ace.rights, ace.ace_type), ace.object_type.clone(), ace.inherited_object_type.clone(), trustee_to_sddl(&ace.trustee)];
+
+    if !ace.application_data.is_empty() {
+        fields.push(ace.application_data.clone());
+    }
+
+    format!("({})", fields.join(";"))
+}
+
+fn trustee_to_sddl(trustee: &str) -> String {
+    sid_alias(trustee).map(|alias| alias.to_string()).unwrap_or(trustee.to_string())
+}
+
+fn acl_to_sddl(entries: &Option<Vec<AccessControlEntry>>, control: DescriptorControl, system: bool) -> String {
+    let flags = match system {
+        true => [(DescriptorControl::SACL_PROTECTED, "P"), (DescriptorControl::SACL_AUTO_INHERIT_REQUIRED, "AR"), (DescriptorControl::SACL_AUTO_INHERITED, "AI")],
+        false => [(DescriptorControl::DACL_PROTECTED, "P"), (DescriptorControl::DACL_AUTO_INHERIT_REQUIRED, "AR"), (DescriptorControl::DACL_AUTO_INHERITED, "AI")]
+    };
+
+    let flags: String = flags.iter().filter(|(flag, _)| control.contains(*flag)).map(|(_, name)| *name).collect();
+
+    match entries {
+        Some(entries) => format!("{}{}", flags, entries.iter().map(ace_to_sddl).collect::<String>()),
+        None => format!("{}{}", flags, NULL_ACL)
+    }
+}
+
+// the canonical form: parts in "O", "G", "D", "S" order, the aliases wherever there is one, upper-case names
+// and lower-case guids.
+impl fmt::Display for SecurityDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(owner) = &self.owner {
+            write!(f, "O:{}", trustee_to_sddl(owner))?;
+        }
+
+        if let Some(group) = &self.group {
+            write!(f, "G:{}", trustee_to_sddl(group))?;
+        }
+
+        if self.dacl.is_some() || self.control.contains(DescriptorControl::DACL_PRESENT) {
+            write!(f, "D:{}", acl_to_sddl(&self.dacl, self.control, false))?;
+        }
+
+        if self.sacl.is_some() || self.control.contains(DescriptorControl::SACL_PRESENT) {
+            write!(f, "S:{}", acl_to_sddl(&self.sacl, self.control, true))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for SecurityDescriptor {
+    type Err = WinFileInfoError;
+
+    fn from_str(sddl: &str) -> Result<SecurityDescriptor, WinFileInfoError> {
+        SecurityDescriptor::parse(sddl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse(){
+        let descriptor = SecurityDescriptor::parse("O:BAG:SYD:PAI(A;OICI;FA;;;SY)(A;;0x1200a9;;;BU)").unwrap();
+        let dacl = descriptor.dacl.as_ref().unwrap();
+
+        assert_eq!(descriptor.owner.as_deref(), Some("S-1-5-32-544"));
+        assert_eq!(descriptor.group.as_deref(), Some("S-1-5-18"));
+        assert!(descriptor.control.contains(DescriptorControl::DACL_PRESENT | DescriptorControl::DACL_PROTECTED | DescriptorControl::DACL_AUTO_INHERITED));
+        assert!(!descriptor.control.contains(DescriptorControl::SACL_PRESENT));
+        assert_eq!(descriptor.sacl, None);
+        assert_eq!(dacl.len(), 2);
+        assert_eq!(dacl[0], AccessControlEntry::new(AceType::Allow, "S-1-5-18", FileSystemRights::FULL_CONTROL, AceFlags::OBJECT_INHERIT | AceFlags::CONTAINER_INHERIT));
+        assert_eq!(dacl[1].trustee, "S-1-5-32-545");
+        assert_eq!(dacl[1].rights.to_string(), "ReadAndExecute, Synchronize");
+    }
+
+    #[test]
+    fn test_canonical_output(){
+        for (input, canonical) in [
+            ("O:BAG:SYD:PAI(A;OICI;FA;;;SY)(A;;0x1200a9;;;BU)", "O:BAG:SYD:PAI(A;OICI;FA;;;SY)(A;;0x1200a9;;;BU)"),
+            ("O:S-1-5-32-544G:S-1-5-18D:AI(A;CIOI;0x1F01FF;;;S-1-5-18)", "O:BAG:SYD:AI(A;OICI;FA;;;SY)"),
+            (" D:P (a;oiciio;ga;;;co) ( A ; ; 2032127 ; ; ; wd ) ", "D:P(A;OICIIO;GA;;;CO)(A;;FA;;;WD)"),
+            ("D:(A;;0xf01ff;;;BA)", "D:(A;;CCDCLCSWRPWPDTLOCRSDRCWDWO;;;BA)"),
+            ("D:(A;;GRGX;;;BU)", "D:(A;;GXGR;;;BU)"),
+            ("D:(A;;0x1000000;;;BA)", "D:(A;;0x1000000;;;BA)"),
+            ("O:DAG:DUD:(A;;FA;;;LA)", "O:DAG:DUD:(A;;FA;;;LA)"),
+            ("D:NO_ACCESS_CONTROL", "D:NO_ACCESS_CONTROL"),
+            ("D:PNO_ACCESS_CONTROL", "D:PNO_ACCESS_CONTROL"),
+            ("D:", "D:"),
+            ("S:ARAI(AU;SAFA;FA;;;WD)(ML;;NWNR;;;LW)", "S:ARAI(AU;SAFA;FA;;;WD)(ML;;NWNR;;;LW)"),
+            ("D:(OA;CI;RPWP;BF967A7F-0DE6-11D0-A285-00AA003049E2;bf967aba-0de6-11d0-a285-00aa003049e2;PS)", "D:(OA;CI;RPWP;bf967a7f-0de6-11d0-a285-00aa003049e2;bf967aba-0de6-11d0-a285-00aa003049e2;PS)"),
+            ("", "")
+        ] {
+            let descriptor = SecurityDescriptor::parse(input).unwrap();
+
+            assert_eq!(descriptor.to_string(), canonical, "{}", input);
+            assert_eq!(SecurityDescriptor::parse(canonical).unwrap(), descriptor, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_null_and_empty_dacl(){
+        let null = SecurityDescriptor::parse("O:SYD:NO_ACCESS_CONTROL").unwrap();
+        let empty = SecurityDescriptor::parse("O:SYD:").unwrap();
+        let absent = SecurityDescriptor::parse("O:SY").unwrap();
+
+        assert_eq!(null.dacl, None);
+        assert!(null.control.contains(DescriptorControl::DACL_PRESENT));
+        assert_eq!(empty.dacl, Some(vec![]));
+        assert_eq!(absent.dacl, None);
+        assert!(!absent.control.contains(DescriptorControl::DACL_PRESENT));
+        assert!(null.to_entity_acl().null_access);
+        assert!(!empty.to_entity_acl().null_access);
+        assert!(!absent.to_entity_acl().null_access);
+    }
+
+    #[test]
+    fn test_conditional_aces(){
+        let sddl = "D:(XA;;FX;;;WD;(@User.Title==\"PM\" && (@User.Division==\"Finance\" || @User.Division==\" Sales;)\")))(A;;FR;;;AU)S:(RA;CI;;;;WD;(\"Project\",TS,0,\"Windows\",\"SQL\"))";
+        let descriptor = SecurityDescriptor::parse(sddl).unwrap();
+        let dacl = descriptor.dacl.as_ref().unwrap();
+        let sacl = descriptor.sacl.as_ref().unwrap();
+
+        assert_eq!(dacl.len(), 2);
+        assert_eq!(dacl[0].ace_type, AceType::AllowCallback);
+        assert_eq!(dacl[0].application_data, "(@User.Title==\"PM\" && (@User.Division==\"Finance\" || @User.Division==\" Sales;)\"))");
+        assert_eq!(dacl[1].ace_type, AceType::Allow);
+        assert_eq!(sacl[0].ace_type, AceType::ResourceAttribute);
+        assert!(sacl[0].rights.is_empty());
+        assert_eq!(sacl[0].application_data, "(\"Project\",TS,0,\"Windows\",\"SQL\")");
+        assert_eq!(descriptor.to_string(), sddl);
+    }
+
+    #[test]
+    fn test_errors(){
+        for sddl in [
+            "X:BA",
+            "O:BAO:SY",
+            "O:XX",
+            "O:S-1-5-x-",
+            "D:(A;;FA;;SY)",
+            "D:(Q;;FA;;;SY)",
+            "D:(A;ZZ;FA;;;SY)",
+            "D:(A;;FAQ;;;SY)",
+            "D:(A;;FA;;;SY",
+            "D:NO_ACCESS_CONTROL(A;;FA;;;SY)",
+            "D:(A;;FA;;;SY)junk"
+        ] {
+            assert!(matches!(SecurityDescriptor::parse(sddl), Err(WinFileInfoError::UnparseableRecord { .. })), "{}", sddl);
+        }
+    }
+
+    #[test]
+    fn test_entity_acl(){
+        let acl = "O:BAG:SYD:PAI(D;;SD;;;BG)(A;OICIID;FA;;;SY)S:(AU;FA;FA;;;WD)".parse::<SecurityDescriptor>().unwrap().to_entity_acl();
+
+        assert_eq!(acl.owner, "S-1-5-32-544");
+        assert!(acl.access_protected);
+        assert_eq!(acl.access.len(), 2);
+        assert_eq!(acl.access[0].ace_type, AceType::Deny);
+        assert!(acl.access[1].is_inherited());
+        assert_eq!(acl.audit[0].flags, AceFlags::FAILED_ACCESS);
+        assert_eq!(acl.sddl, "O:BAG:SYD:PAI(D;;SD;;;BG)(A;OICIID;FA;;;SY)S:(AU;FA;FA;;;WD)");
+    }
+}
@@ -0,0 +1,263 @@
+use std::{io::{BufRead, BufReader, Error, ErrorKind, Write}, process::{Child, ChildStdin, ChildStdout, Command, Stdio}, sync::Mutex, thread, time::{Duration, Instant}};
+
+use crate::{error::WinFileInfoError, runner::{CommandOutput, CommandRunner}};
+
+// script of the shell side of a session. it reads a base64 command per line, runs it and answers with a single
+// "#frame <success> <stdout> <stderr>" line whose outputs are base64 too, so nothing inside of a file name can
+// break the framing. lines that don't start with "#frame " are ignored, like banners or "chcp" output.
+const SESSION_SCRIPT: &str = "[Console]::OutputEncoding = [Text.Encoding]::UTF8; $utf8 = New-Object Text.UTF8Encoding $false; while ($null -ne ($line = [Console]::In.ReadLine())) { $command = $utf8.GetString([Convert]::FromBase64String($line)); $Error.Clear(); $output = ''; try { $output = Invoke-Expression $command 2>$null | Out-String } catch { }; $errors = ($Error | ForEach-Object { $_ | Out-String }) -join ''; $success = $(if ($Error.Count -eq 0) { 1 } else { 0 }); [Console]::Out.WriteLine('#frame ' + $success + ' ' + [Convert]::ToBase64String($utf8.GetBytes([string]$output)) + ' ' + [Convert]::ToBase64String($utf8.GetBytes($errors))); [Console]::Out.Flush() }";
+
+const FRAME_PREFIX: &str = "#frame ";
+
+// how long a dropped session has to exit by itself before it's killed.
+const EXIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+// a long-lived shell process that serves every command sent to it, so we pay the startup cost of powershell
+// only once. it's a "CommandRunner", so every "_with" function can use it:
+//
+// let session = Session::start().unwrap();
+//
+// let desktop = other_folder_info_with(&session, "C:\\Users\\necoo\\Desktop").unwrap();
+// let onedrive = entity_kind_with(&session, "C:\\Users\\necoo\\OneDrive").unwrap();
+//
+// commands are run one by one. the process is closed when the session is dropped, and killed if it doesn't exit in a
+// second.
+pub struct Session {
+    process: Mutex<SessionProcess>
+}
+
+struct SessionProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>
+}
+
+impl Session {
+    // starts a powershell session.
+    pub fn start() -> Result<Session, WinFileInfoError> {
+        let mut command = Command::new("powershell");
+        command.args(["-NoLogo", "-NoProfile", "-NonInteractive", "-EncodedCommand", &encoded_command(SESSION_SCRIPT)]);
+
+        Session::spawn(command)
+    }
+
+    // starts a session with any process which speaks the same framing, like a stand-in script in the tests.
+    pub fn spawn(mut command: Command) -> Result<Session, WinFileInfoError> {
+        let mut child = command.stdin(Stdio::piped())
+                               .stdout(Stdio::piped())
+                               .stderr(Stdio::null())
+                               .spawn()
+                               .map_err(WinFileInfoError::ShellNotAvailable)?;
+
+        let (stdin, stdout) = match (child.stdin.take(), child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => (stdin, stdout),
+            _ => return Err(WinFileInfoError::ShellNotAvailable(Error::new(ErrorKind::BrokenPipe, "cannot reach the pipes of the session")))
+        };
+
+        Ok(Session { process: Mutex::new(SessionProcess { child, stdin: Some(stdin), stdout: BufReader::new(stdout) }) })
+    }
+}
+
+impl CommandRunner for Session {
+    fn run(&self, command: &str) -> Result<CommandOutput, Error> {
+        let mut process = self.process.lock().map_err(|_| Error::other("session is poisoned by a panic"))?;
+
+        match process.stdin.as_mut() {
+            Some(stdin) => {
+                stdin.write_all(format!("{}\n", base64_encode(command.as_bytes())).as_bytes())?;
+                stdin.flush()?;
+            },
+            None => return Err(Error::new(ErrorKind::BrokenPipe, "session is closed"))
+        }
+
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+
+            if process.stdout.read_line(&mut line)? == 0 {
+                return Err(Error::new(ErrorKind::UnexpectedEof, "session process exited"));
+            }
+
+            if let Some(frame) = line.trim_end_matches(['\r', '\n']).strip_prefix(FRAME_PREFIX) {
+                return parse_frame(frame);
+            }
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Ok(process) = self.process.get_mut() {
+            // closing stdin ends the loop of the shell. we give it "EXIT_TIMEOUT" to do that and kill it after, when
+            // it's stuck in a command.
+            process.stdin.take();
+
+            let started = Instant::now();
+
+            while let Ok(None) = process.child.try_wait() {
+                if started.elapsed() >= EXIT_TIMEOUT {
+                    let _ = process.child.kill();
+                    break;
+                }
+
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            let _ = process.child.wait();
+        }
+    }
+}
+
+fn parse_frame(frame: &str) -> Result<CommandOutput, Error> {
+    let parts: Vec<&str> = frame.split(' ').collect();
+
+    match parts.as_slice() {
+        [success, stdout, stderr] => Ok(CommandOutput {
+            stdout: base64_decode(stdout)?,
+            stderr: base64_decode(stderr)?,
+            success: *success == "1"
+        }),
+        _ => Err(Error::new(ErrorKind::InvalidData, format!("invalid session frame: {}", frame)))
+    }
+}
+
+// "-EncodedCommand" takes the base64 of the utf-16le script.
+fn encoded_command(script: &str) -> String {
+    let bytes: Vec<u8> = script.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect();
+
+    base64_encode(&bytes)
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+
+        for index in 0..4 {
+            match index <= chunk.len() {
+                true => encoded.push(BASE64_ALPHABET[(group >> (18 - index * 6) & 0x3F) as usize] as char),
+                false => encoded.push('=')
+            }
+        }
+    }
+
+    encoded
+}
+
+fn base64_decode(encoded: &str) -> Result<Vec<u8>, Error> {
+    let invalid = || Error::new(ErrorKind::InvalidData, format!("invalid base64 in session frame: {}", encoded));
+    let encoded = encoded.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    let (mut group, mut bits) = (0u32, 0);
+
+    for character in encoded.bytes() {
+        let value = BASE64_ALPHABET.iter().position(|letter| *letter == character).ok_or_else(invalid)?;
+
+        group = group << 6 | value as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((group >> bits) as u8);
+        }
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64(){
+        let samples: [&[u8]; 5] = [b"", b"a", b"ab", b"abc", "C:\\report : final 日本語 🙂.txt\n'\"".as_bytes()];
+
+        for sample in samples {
+            assert_eq!(base64_decode(&base64_encode(sample)).unwrap(), sample);
+        }
+
+        assert_eq!(base64_encode(b"Get-Item"), "R2V0LUl0ZW0=");
+        assert_eq!(encoded_command("ls"), "bABzAA==");
+        assert!(base64_decode("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_parse_frame(){
+        let output = parse_frame("0  R2V0LUl0ZW0=").unwrap();
+
+        assert_eq!(output, CommandOutput::new(vec![], b"Get-Item".to_vec(), false));
+        assert!(parse_frame("1 a").is_err());
+    }
+
+    #[cfg(unix)]
+    fn stand_in_session() -> Session {
+        let mut command = Command::new("sh");
+        command.arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/session_stand_in.sh"));
+
+        Session::spawn(command).unwrap()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_session_with_stand_in(){
+        let session = stand_in_session();
+        let command = "Write-Output 'report : final 日本語 🙂.txt'\n#frame 1 x y\r\n";
+
+        let output = session.run(command).unwrap();
+
+        assert!(output.success);
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), command);
+        assert_eq!(session.run("Get-Count").unwrap().stdout, b"2");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_queries_through_session(){
+        let session = stand_in_session();
+
+        let entities = crate::other_folder_info_with(&session, "C:\\Users\\necoo\\Desktop").unwrap();
+        let missing = crate::entity_info_with(&session, "C:\\missing");
+
+        assert_eq!(entities.len(), 4);
+        assert!(matches!(missing, Err(WinFileInfoError::PathNotFound { .. })));
+        // every query went through the same process:
+        assert_eq!(session.run("Get-Count").unwrap().stdout, b"3");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_drop(){
+        let marker = std::env::temp_dir().join(format!("win-file-info-session-{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+
+        // a process which exits by itself when its stdin is closed isn't killed:
+        let mut command = Command::new("sh");
+        command.args(["-c", "cat > /dev/null; touch \"$0\""]).arg(&marker);
+
+        drop(Session::spawn(command).unwrap());
+
+        assert!(marker.exists());
+        std::fs::remove_file(&marker).unwrap();
+
+        // a stuck one is killed after the timeout:
+        let mut command = Command::new("sh");
+        command.args(["-c", "sleep 30"]);
+
+        let started = Instant::now();
+        drop(Session::spawn(command).unwrap());
+
+        assert!(started.elapsed() >= EXIT_TIMEOUT && started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_session_that_cannot_start(){
+        let result = Session::spawn(Command::new("there-is-no-such-shell"));
+
+        assert!(matches!(result, Err(WinFileInfoError::ShellNotAvailable(_))));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::{error::WinFileInfoError, path::WindowsPath, runner::CommandRunner, WindowsEntity};
+
+// 8.3 aliases which ntfs gives to the long names, like "PROGRA~1" for "Program Files". the first four entities
+// with the same start get "~1" to "~4", the later ones get two characters, a hash of their long name and "~1":
+//
+// let short_name = generate_short_name("Program Files", &["PROGRA~1"]).unwrap();
+//
+// assert_eq!(short_name, "PROGRA~2");
+//
+// the characters which are not ascii become "_", like on the systems whose oem code page doesn't have them.
+
+// characters that can be in a short name besides the ascii letters and digits.
+const SHORT_NAME_SYMBOLS: &str = "!#$%&'()-@^_`{}~";
+
+fn short_name_character(character: char) -> char {
+    match character.is_ascii_alphanumeric() || SHORT_NAME_SYMBOLS.contains(character) {
+        true => character.to_ascii_uppercase(),
+        false => '_'
+    }
+}
+
+// stem and extension of a name as they are used for 8.3, the last dot separates them unless it's a leading one.
+fn split_name(name: &str) -> (&str, &str) {
+    let trimmed = name.trim_start_matches('.');
+
+    match trimmed.rsplit_once('.') {
+        Some((_, extension)) => (&name[..name.len() - extension.len() - 1], extension),
+        None => (name, "")
+    }
+}
+
+// true when the name fits to 8.3 as it is, so ntfs doesn't give it an alias. the case doesn't matter.
+pub fn fits_short_name(name: &str) -> bool {
+    let (stem, extension) = split_name(name);
+    let valid = |part: &str| part.chars().all(|character| character != '.' && character != ' ' && short_name_character(character) == character.to_ascii_uppercase());
+
+    (1..=8).contains(&stem.len()) && extension.len() <= 3 && !name.ends_with('.') && valid(stem) && valid(extension)
+}
+
+// true when the component looks like an alias which ntfs generated, like "PROGRA~1", "MICROS~2.TXT" or
+// "TE021F~1.TXT". names like that can still be long names of their own, "fits_short_name()" is true for them too.
+pub fn is_short_name(component: &str) -> bool {
+    if !fits_short_name(component) {
+        return false;
+    }
+
+    let (stem, _) = split_name(component);
+
+    match stem.rsplit_once('~') {
+        Some((start, number)) => !start.is_empty() && !number.is_empty() && number.chars().all(|character| character.is_ascii_digit()) && !number.starts_with('0'),
+        None => false
+    }
+}
+
+// the alias that ntfs gives to "name" in a directory which already has the "siblings", which can be the short and
+// the long names there. "None" when the name fits to 8.3 and gets no alias.
+pub fn generate_short_name(name: &str, siblings: &[&str]) -> Option<String> {
+    if fits_short_name(name) {
+        return None;
+    }
+
+    let clean = |part: &str| -> String { part.chars().filter(|character| *character != '.' && *character != ' ').map(short_name_character).collect() };
+    let (stem, extension) = split_name(name);
+    let stem = clean(stem);
+    let extension: String = clean(extension).chars().take(3).collect();
+
+    // a name like "..." has nothing to make an alias from.
+    if stem.is_empty() && extension.is_empty() {
+        return None;
+    }
+
+    let stem = match stem.is_empty() {
+        true => "_".to_string(),
+        false => stem
+    };
+
+    let taken = |candidate: &str| siblings.iter().any(|sibling| sibling.eq_ignore_ascii_case(candidate));
+    let with_extension = |base: String| match extension.is_empty() {
+        true => base,
+        false => format!("{}.{}", base, extension)
+    };
+
+    for number in 1..=4 {
+        let candidate = with_extension(format!("{}~{}", stem.chars().take(6).collect::<String>(), number));
+
+        if !taken(&candidate) {
+            return Some(candidate);
+        }
+    }
+
+    let hash = format!("{:04X}", name_hash(name));
+
+    for number in 1..1_000_000u32 {
+        let suffix = format!("{}~{}", hash, number);
+        let start: String = stem.chars().take(8usize.saturating_sub(suffix.len()).min(2)).collect();
+        let candidate = with_extension(format!("{}{}", start, suffix));
+
+        if !taken(&candidate) {
+            return Some(candidate);
+        }
+    }
+
+    None
+}
+
+// checksum of the long name that goes into the aliases after "~4", over its utf-16 units like
+// "RtlGenerate8dot3Name" does it: a base 37 sum, scrambled by a multiplication and "% 1000000007" with the
+// integer overflows of windows, and its four hex digits in the reverse order.
+fn name_hash(name: &str) -> u16 {
+    let checksum = name.encode_utf16().fold(0i32, |checksum, unit| checksum.wrapping_mul(0x25).wrapping_add(unit as i32));
+    let scrambled = checksum.wrapping_mul(314_159_269).wrapping_abs();
+
+    // windows divides by 1000000007 with a multiplication and a shift. "i32::MIN" stays negative after the
+    // absolute value, the casts give the same wrong quotient as windows for it.
+    let quotient = (scrambled as i64 as u64).wrapping_mul(1_152_921_497) >> 60;
+    let hash = (scrambled as i64 as u64).wrapping_sub(quotient.wrapping_mul(1_000_000_007)) as u16;
+
+    (hash & 0xF000) >> 12 | (hash & 0x0F00) >> 4 | (hash & 0x00F0) << 4 | (hash & 0x000F) << 12
+}
+
+// aliases of the entities of a directory listing, in the order of the listing. ntfs gives them in the order the
+// entities were created, so that's how they are given here. it can differ from the real ones if some entities
+// were renamed or deleted since then.
+pub fn short_names(entities: &[WindowsEntity]) -> Vec<Option<String>> {
+    let mut order: Vec<usize> = (0..entities.len()).collect();
+    order.sort_by_key(|index| entities[*index].creation_time.map(|time| time.filetime()).unwrap_or(u64::MAX));
+
+    let mut taken: Vec<String> = entities.iter().filter(|entity| fits_short_name(&entity.name)).map(|entity| entity.name.clone()).collect();
+    let mut aliases = vec![None; entities.len()];
+
+    for index in order {
+        let siblings: Vec<&str> = taken.iter().map(|name| name.as_str()).collect();
+
+        if let Some(alias) = generate_short_name(&entities[index].name, &siblings) {
+            taken.push(alias.clone());
+            aliases[index] = Some(alias);
+        }
+    }
+
+    aliases
+}
+
+// entity of the listing which "component" is the name or the alias of. an entity whose long name is the component
+// wins over an alias.
+pub fn resolve_short_name<'a>(component: &str, entities: &'a [WindowsEntity]) -> Option<&'a WindowsEntity> {
+    if let Some(entity) = entities.iter().find(|entity| entity.name.eq_ignore_ascii_case(component)) {
+        return Some(entity);
+    }
+
+    let aliases = short_names(entities);
+
+    entities.iter().zip(aliases).find(|(_, alias)| alias.as_deref().is_some_and(|alias| alias.eq_ignore_ascii_case(component))).map(|(entity, _)| entity)
+}
+
+#[cfg(target_os = "windows")]
+pub fn expand_short_path(path: &str) -> Result<String, WinFileInfoError> {
+    expand_short_path_with(&crate::runner::PowerShellRunner, path)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn expand_short_path(path: &str) -> Result<String, WinFileInfoError> {
+    expand_short_path_using(path, crate::native::other_folder_info)
+}
+
+// "C:\PROGRA~1\MICROS~2" to "C:\Program Files\Microsoft Office", every component that looks like an alias is
+// looked up in the listing of its parent. the other components are kept as they are.
+pub fn expand_short_path_with<R: CommandRunner>(runner: &R, path: &str) -> Result<String, WinFileInfoError> {
+    expand_short_path_using(path, |parent| crate::other_folder_info_with(runner, parent))
+}
+
+fn expand_short_path_using<F: FnMut(&str) -> Result<Vec<WindowsEntity>, WinFileInfoError>>(path: &str, mut list: F) -> Result<String, WinFileInfoError> {
+    let parsed = WindowsPath::parse(path)?.normalize();
+    let mut expanded = parsed.clone();
+
+    while let Some(parent) = expanded.parent() {
+        expanded = parent;
+    }
+
+    for component in parsed.components() {
+        let name = match is_short_name(component) {
+            true => {
+                let parent = expanded.to_string();
+                let entities = list(&parent)?;
+
+                match resolve_short_name(component, &entities) {
+                    Some(entity) => entity.name.clone(),
+                    None => return Err(WinFileInfoError::PathNotFound { path: path.to_string(), stderr: None })
+                }
+            },
+            false => component.clone()
+        };
+
+        expanded = expanded.join(&name)?;
+    }
+
+    Ok(expanded.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{runner::FixtureRunner, FileAttributes, WindowsTimestamp};
+
+    fn entity(name: &str, created: u64) -> WindowsEntity {
+        WindowsEntity {
+            mode: vec![],
+            types: vec![],
+            owner: "".to_string(),
+            owner_sid: None,
+            last_write_time: None,
+            name: name.to_string(),
+            creation_time: Some(WindowsTimestamp::from_filetime(created, None)),
+            attributes: FileAttributes::DIRECTORY,
+            last_access_time: None,
+            size: None,
+            absolute_path: name.to_string(),
+            directory_size: None
+        }
+    }
+
+    #[test]
+    fn test_fits_and_looks_short(){
+        for name in ["README.TXT", "readme.txt", "A", "CON_1.C", "PROGRA~1", "TE021F~1.TXT"] {
+            assert!(fits_short_name(name), "{}", name);
+        }
+
+        for name in ["Program Files", "notes.text", "a.b.c", ".gitignore", "a+b.txt", "ninechars", "é.txt", "trailing."] {
+            assert!(!fits_short_name(name), "{}", name);
+        }
+
+        assert!(is_short_name("PROGRA~1"));
+        assert!(is_short_name("micros~2.doc"));
+        assert!(!is_short_name("README.TXT"));
+        assert!(!is_short_name("~1"));
+        assert!(!is_short_name("PROGRA~0"));
+        assert!(!is_short_name("Program Files"));
+    }
+
+    #[test]
+    fn test_generate_short_name(){
+        assert_eq!(generate_short_name("readme.txt", &[]), None);
+        assert_eq!(generate_short_name("Program Files", &[]).unwrap(), "PROGRA~1");
+        assert_eq!(generate_short_name("Program Files (x86)", &["PROGRA~1"]).unwrap(), "PROGRA~2");
+        assert_eq!(generate_short_name("index.html", &[]).unwrap(), "INDEX~1.HTM");
+        assert_eq!(generate_short_name(".gitignore", &[]).unwrap(), "GITIGN~1");
+        assert_eq!(generate_short_name("a+b [1].jpeg", &[]).unwrap(), "A_B_1_~1.JPE");
+        assert_eq!(generate_short_name("my.long.file.name.txt", &[]).unwrap(), "MYLONG~1.TXT");
+        assert_eq!(generate_short_name("...", &[]), None);
+
+        let taken = ["TEXTFI~1.TXT", "TEXTFI~2.TXT", "TEXTFI~3.TXT", "TEXTFI~4.TXT"];
+        let fifth = generate_short_name("textfile five.txt", &taken).unwrap();
+
+        assert_eq!(fifth, "TED5DC~1.TXT");
+        assert!(is_short_name(&fifth));
+        assert_eq!(generate_short_name("textfile six.txt", &taken).unwrap(), "TEACD0~1.TXT");
+
+        let with_fifth = [&taken[..], &[fifth.as_str()]].concat();
+        assert_eq!(generate_short_name("textfile five.txt", &with_fifth).unwrap(), "TED5DC~2.TXT");
+    }
+
+    #[test]
+    fn test_name_hash(){
+        assert_eq!(name_hash(""), 0);
+        assert_eq!(name_hash("a"), 0x58EE);
+        assert_eq!(name_hash("ab"), 0x69D1);
+        assert_eq!(name_hash("TextFile.Mine.txt"), 0x2AF5);
+        assert_eq!(name_hash("Microsoft Visual Studio 2022"), 0x53BA);
+    }
+
+    #[test]
+    fn test_short_names_follow_creation_order(){
+        let entities = [entity("Microsoft Office", 30), entity("Microsoft.NET", 10), entity("README.TXT", 5), entity("Microsoft Visual Studio", 20)];
+
+        assert_eq!(short_names(&entities), [Some("MICROS~2".to_string()), Some("MICROS~1.NET".to_string()), None, Some("MICROS~1".to_string())]);
+        assert_eq!(resolve_short_name("micros~2", &entities).unwrap().name, "Microsoft Office");
+        assert_eq!(resolve_short_name("README.TXT", &entities).unwrap().name, "README.TXT");
+        assert!(resolve_short_name("MICROS~3", &entities).is_none());
+    }
+
+    #[test]
+    fn test_expand_short_path(){
+        let listing = |names: &[&str]| format!("[{}]", names.iter().enumerate().map(|(index, name)| format!("{{\"Mode\":\"d----\",\"Owner\":\"\",\"Name\":\"{}\",\"CreationTime\":\"2024-01-0{}T00:00:00.0000000Z\",\"Attributes\":16,\"FullName\":\"{}\"}}", name, index + 1, name)).collect::<Vec<String>>().join(","));
+        let runner = FixtureRunner::new().with_stdout(listing(&["Program Files", "Program Files (x86)"])).with_stdout(listing(&["Microsoft Office", "Microsoft Visual Studio"]));
+
+        assert_eq!(expand_short_path_with(&runner, "C:\\PROGRA~1\\MICROS~2\\Office16").unwrap(), "C:\\Program Files\\Microsoft Visual Studio\\Office16");
+
+        let commands = runner.commands();
+
+        assert!(commands[0].starts_with("Get-ChildItem -LiteralPath 'C:\\' |"));
+        assert!(commands[1].starts_with("Get-ChildItem -LiteralPath 'C:\\Program Files' |"));
+        assert!(matches!(expand_short_path_with(&FixtureRunner::new().with_stdout("[]"), "C:\\PROGRA~9"), Err(WinFileInfoError::PathNotFound { .. })));
+        assert_eq!(expand_short_path_with(&FixtureRunner::new(), "C:\\Users\\necoo").unwrap(), "C:\\Users\\necoo");
+    }
+}
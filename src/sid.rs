@@ -0,0 +1,369 @@
+use std::{fmt, str::FromStr};
+
+use crate::{error::WinFileInfoError, sddl};
+
+// a security identifier, "S-1-5-32-544" in its string form. it can be read from and written to both the string
+// form and the binary form that windows keeps in the security descriptors:
+//
+// let sid = Sid::parse("S-1-5-21-3623811015-3361044348-30300820-1013").unwrap();
+//
+// assert_eq!(sid.rid(), Some(1013));
+// assert_eq!(sid.domain().unwrap().to_string(), "S-1-5-21-3623811015-3361044348-30300820");
+// assert_eq!(Sid::from_bytes(&sid.to_bytes()).unwrap(), sid);
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Sid {
+    revision: u8,
+    // 48 bits in windows, like "5" of "NT AUTHORITY".
+    authority: u64,
+    sub_authorities: Vec<u32>
+}
+
+// windows doesn't allow more than that.
+pub const MAX_SUB_AUTHORITIES: usize = 15;
+
+// the sids which are the same on every machine, with the names windows gives them in english.
+const WELL_KNOWN_SIDS: [(&str, &str); 62] = [
+    ("S-1-0-0", "NULL SID"),
+    ("S-1-1-0", "Everyone"),
+    ("S-1-2-0", "LOCAL"),
+    ("S-1-2-1", "CONSOLE LOGON"),
+    ("S-1-3-0", "CREATOR OWNER"),
+    ("S-1-3-1", "CREATOR GROUP"),
+    ("S-1-3-4", "OWNER RIGHTS"),
+    ("S-1-5-1", "NT AUTHORITY\\DIALUP"),
+    ("S-1-5-2", "NT AUTHORITY\\NETWORK"),
+    ("S-1-5-3", "NT AUTHORITY\\BATCH"),
+    ("S-1-5-4", "NT AUTHORITY\\INTERACTIVE"),
+    ("S-1-5-6", "NT AUTHORITY\\SERVICE"),
+    ("S-1-5-7", "NT AUTHORITY\\ANONYMOUS LOGON"),
+    ("S-1-5-9", "NT AUTHORITY\\ENTERPRISE DOMAIN CONTROLLERS"),
+    ("S-1-5-10", "NT AUTHORITY\\SELF"),
+    ("S-1-5-11", "NT AUTHORITY\\Authenticated Users"),
+    ("S-1-5-12", "NT AUTHORITY\\RESTRICTED"),
+    ("S-1-5-13", "NT AUTHORITY\\TERMINAL SERVER USER"),
+    ("S-1-5-14", "NT AUTHORITY\\REMOTE INTERACTIVE LOGON"),
+    ("S-1-5-15", "NT AUTHORITY\\This Organization"),
+    ("S-1-5-17", "NT AUTHORITY\\IUSR"),
+    ("S-1-5-18", "NT AUTHORITY\\SYSTEM"),
+    ("S-1-5-19", "NT AUTHORITY\\LOCAL SERVICE"),
+    ("S-1-5-20", "NT AUTHORITY\\NETWORK SERVICE"),
+    ("S-1-5-32-544", "BUILTIN\\Administrators"),
+    ("S-1-5-32-545", "BUILTIN\\Users"),
+    ("S-1-5-32-546", "BUILTIN\\Guests"),
+    ("S-1-5-32-547", "BUILTIN\\Power Users"),
+    ("S-1-5-32-548", "BUILTIN\\Account Operators"),
+    ("S-1-5-32-549", "BUILTIN\\Server Operators"),
+    ("S-1-5-32-550", "BUILTIN\\Print Operators"),
+    ("S-1-5-32-551", "BUILTIN\\Backup Operators"),
+    ("S-1-5-32-552", "BUILTIN\\Replicator"),
+    ("S-1-5-32-555", "BUILTIN\\Remote Desktop Users"),
+    ("S-1-5-32-556", "BUILTIN\\Network Configuration Operators"),
+    ("S-1-5-32-558", "BUILTIN\\Performance Monitor Users"),
+    ("S-1-5-32-559", "BUILTIN\\Performance Log Users"),
+    ("S-1-5-32-562", "BUILTIN\\Distributed COM Users"),
+    ("S-1-5-32-568", "BUILTIN\\IIS_IUSRS"),
+    ("S-1-5-32-569", "BUILTIN\\Cryptographic Operators"),
+    ("S-1-5-32-573", "BUILTIN\\Event Log Readers"),
+    ("S-1-5-32-578", "BUILTIN\\Hyper-V Administrators"),
+    ("S-1-5-32-580", "BUILTIN\\Remote Management Users"),
+    ("S-1-5-80-0", "NT SERVICE\\ALL SERVICES"),
+    ("S-1-5-80-956008885-3418522649-1831038044-1853292631-2271478464", "NT SERVICE\\TrustedInstaller"),
+    ("S-1-5-113", "NT AUTHORITY\\Local account"),
+    ("S-1-5-114", "NT AUTHORITY\\Local account and member of Administrators group"),
+    ("S-1-15-2-1", "APPLICATION PACKAGE AUTHORITY\\ALL APPLICATION PACKAGES"),
+    ("S-1-15-2-2", "APPLICATION PACKAGE AUTHORITY\\ALL RESTRICTED APPLICATION PACKAGES"),
+    ("S-1-15-3-1", "APPLICATION PACKAGE AUTHORITY\\Your Internet connection"),
+    ("S-1-15-3-2", "APPLICATION PACKAGE AUTHORITY\\Your Internet connection, including incoming connections from the Internet"),
+    ("S-1-15-3-3", "APPLICATION PACKAGE AUTHORITY\\Your home or work networks"),
+    ("S-1-15-3-4", "APPLICATION PACKAGE AUTHORITY\\Your pictures library"),
+    ("S-1-15-3-5", "APPLICATION PACKAGE AUTHORITY\\Your videos library"),
+    ("S-1-15-3-6", "APPLICATION PACKAGE AUTHORITY\\Your music library"),
+    ("S-1-15-3-7", "APPLICATION PACKAGE AUTHORITY\\Your documents library"),
+    ("S-1-15-3-8", "APPLICATION PACKAGE AUTHORITY\\Your Windows credentials"),
+    ("S-1-15-3-9", "APPLICATION PACKAGE AUTHORITY\\Software and hardware certificates or a smart card"),
+    ("S-1-15-3-10", "APPLICATION PACKAGE AUTHORITY\\Removable storage"),
+    ("S-1-16-4096", "Mandatory Label\\Low Mandatory Level"),
+    ("S-1-16-8192", "Mandatory Label\\Medium Mandatory Level"),
+    ("S-1-16-12288", "Mandatory Label\\High Mandatory Level")
+];
+
+// the accounts and groups that every domain, or every machine for the local ones, has with the same rid.
+const WELL_KNOWN_RIDS: [(u32, &str); 14] = [
+    (500, "Administrator"),
+    (501, "Guest"),
+    (502, "krbtgt"),
+    (503, "DefaultAccount"),
+    (504, "WDAGUtilityAccount"),
+    (512, "Domain Admins"),
+    (513, "Domain Users"),
+    (514, "Domain Guests"),
+    (515, "Domain Computers"),
+    (516, "Domain Controllers"),
+    (517, "Cert Publishers"),
+    (518, "Schema Admins"),
+    (519, "Enterprise Admins"),
+    (520, "Group Policy Creator Owners")
+];
+
+impl Sid {
+    pub fn new(authority: u64, sub_authorities: &[u32]) -> Result<Sid, WinFileInfoError> {
+        let sid = Sid { revision: 1, authority, sub_authorities: sub_authorities.to_vec() };
+
+        match (authority < 1 << 48, sub_authorities.len() <= MAX_SUB_AUTHORITIES) {
+            (true, true) => Ok(sid),
+            (false, _) => Err(WinFileInfoError::unparseable(sid.to_string(), "identifier authority doesn't fit in 48 bits")),
+            (_, false) => Err(WinFileInfoError::unparseable(sid.to_string(), format!("sid has more than {} sub authorities", MAX_SUB_AUTHORITIES)))
+        }
+    }
+
+    // "S-1-5-18", case doesn't matter. the authorities which don't fit in 32 bits are in hex, like
+    // "S-1-0x123456789ABC-1".
+    pub fn parse(sid: &str) -> Result<Sid, WinFileInfoError> {
+        let error = |reason: &str| WinFileInfoError::unparseable(sid, reason);
+        let parts: Vec<&str> = sid.trim().split('-').collect();
+
+        if parts.len() < 3 || !parts[0].eq_ignore_ascii_case("S") {
+            return Err(error("sid doesn't look like 'S-1-...'"));
+        }
+
+        let revision = parts[1].parse::<u8>().map_err(|_| error("revision of the sid is not a number"))?;
+
+        if revision != 1 {
+            return Err(error("only the revision 1 sids are known"));
+        }
+
+        let authority = match parts[2].strip_prefix("0x").or_else(|| parts[2].strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => parts[2].parse::<u64>()
+        }.map_err(|_| error("identifier authority of the sid is not a number"))?;
+
+        let sub_authorities = parts[3..].iter().map(|part| match part.starts_with(|character: char| character.is_ascii_digit()) {
+            true => part.parse::<u32>().map_err(|_| error("sub authority of the sid is not a 32 bit number")),
+            false => Err(error("sub authority of the sid is not a number"))
+        }).collect::<Result<Vec<u32>, _>>()?;
+
+        Sid::new(authority, &sub_authorities).map_err(|_| error("sid is too long or its authority is too big"))
+    }
+
+    // the binary form: revision, count of the sub authorities, 6 bytes of big-endian authority and the sub
+    // authorities as little-endian u32s. bytes after the sid are not an error, like in a security descriptor.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Sid, WinFileInfoError> {
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let error = |reason: &str| WinFileInfoError::unparseable(hex.clone(), reason);
+
+        if bytes.len() < 8 {
+            return Err(error("sid is shorter than 8 bytes"));
+        }
+
+        let count = bytes[1] as usize;
+
+        if bytes[0] != 1 {
+            return Err(error("only the revision 1 sids are known"));
+        }
+
+        if count > MAX_SUB_AUTHORITIES {
+            return Err(error(&format!("sid has more than {} sub authorities", MAX_SUB_AUTHORITIES)));
+        }
+
+        if bytes.len() < 8 + count * 4 {
+            return Err(error("sid is shorter than its sub authorities"));
+        }
+
+        let authority = bytes[2..8].iter().fold(0u64, |authority, byte| authority << 8 | *byte as u64);
+        let sub_authorities: Vec<u32> = bytes[8..8 + count * 4].chunks(4).map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect();
+
+        Ok(Sid { revision: 1, authority, sub_authorities })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.revision, self.sub_authorities.len() as u8];
+
+        bytes.extend_from_slice(&self.authority.to_be_bytes()[2..]);
+
+        for sub_authority in &self.sub_authorities {
+            bytes.extend_from_slice(&sub_authority.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn authority(&self) -> u64 {
+        self.authority
+    }
+
+    pub fn sub_authorities(&self) -> &[u32] {
+        &self.sub_authorities
+    }
+
+    // the last sub authority of the account and group sids, the number of the account in its domain.
+    pub fn rid(&self) -> Option<u32> {
+        match self.is_domain_account() || self.is_builtin() {
+            true => self.sub_authorities.last().copied(),
+            false => None
+        }
+    }
+
+    // the sid of the domain or the machine that the account belongs to, "S-1-5-21-x-y-z" for
+    // "S-1-5-21-x-y-z-1001" and "S-1-5-32" for the builtin groups.
+    pub fn domain(&self) -> Option<Sid> {
+        match self.is_domain_account() || self.is_builtin() {
+            true => Some(Sid { revision: self.revision, authority: self.authority, sub_authorities: self.sub_authorities[..self.sub_authorities.len() - 1].to_vec() }),
+            false => None
+        }
+    }
+
+    // an account or a group of a domain or of a machine, "S-1-5-21-x-y-z-rid".
+    pub fn is_domain_account(&self) -> bool {
+        self.authority == 5 && self.sub_authorities.len() == 5 && self.sub_authorities[0] == 21
+    }
+
+    // the groups of the "BUILTIN" domain, like "S-1-5-32-544".
+    pub fn is_builtin(&self) -> bool {
+        self.authority == 5 && self.sub_authorities.len() == 2 && self.sub_authorities[0] == 32
+    }
+
+    // the sids of the services, "S-1-5-80-..." made from the hash of the service name.
+    pub fn is_service(&self) -> bool {
+        self.authority == 5 && self.sub_authorities.first() == Some(&80)
+    }
+
+    // the capabilities of the app packages, like "S-1-15-3-1" for the internet access.
+    pub fn is_capability(&self) -> bool {
+        self.authority == 15 && self.sub_authorities.first() == Some(&3)
+    }
+
+    // the english name of a well-known sid like "BUILTIN\Administrators". the well-known accounts of the domains
+    // come without their domain, like "Domain Admins", since we can't know its name. the machines which are not
+    // in a domain call "Domain Users" "None".
+    pub fn well_known_name(&self) -> Option<&'static str> {
+        let sid = self.to_string();
+
+        if let Some((_, name)) = WELL_KNOWN_SIDS.iter().find(|(well_known, _)| *well_known == sid) {
+            return Some(name);
+        }
+
+        match self.is_domain_account() {
+            true => WELL_KNOWN_RIDS.iter().find(|(rid, _)| Some(*rid) == self.rid()).map(|(_, name)| *name),
+            false => None
+        }
+    }
+
+    pub fn is_well_known(&self) -> bool {
+        self.well_known_name().is_some()
+    }
+
+    // the other way of "well_known_name", the sid of a name like "NT AUTHORITY\SYSTEM" or "Everyone". case
+    // doesn't matter, like in windows.
+    pub fn from_well_known_name(name: &str) -> Option<Sid> {
+        WELL_KNOWN_SIDS.iter().find(|(_, well_known)| well_known.eq_ignore_ascii_case(name)).and_then(|(sid, _)| Sid::parse(sid).ok())
+    }
+
+    // the sid of an sddl alias like "BA", "None" for the domain aliases like "DA".
+    pub fn from_sddl_alias(alias: &str) -> Option<Sid> {
+        sddl::alias_sid(alias).and_then(|sid| Sid::parse(sid).ok())
+    }
+
+    pub fn sddl_alias(&self) -> Option<&'static str> {
+        sddl::sid_alias(&self.to_string())
+    }
+}
+
+// "S-1-5-21-3623811015-3361044348-30300820-1013", the big authorities in 12 lower-case hex digits like windows
+// writes them, "S-1-0x123456789abc-1".
+impl fmt::Display for Sid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.authority < 1 << 32 {
+            true => write!(f, "S-{}-{}", self.revision, self.authority)?,
+            false => write!(f, "S-{}-0x{:012x}", self.revision, self.authority)?
+        }
+
+        for sub_authority in &self.sub_authorities {
+            write!(f, "-{}", sub_authority)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Sid {
+    type Err = WinFileInfoError;
+
+    fn from_str(sid: &str) -> Result<Sid, WinFileInfoError> {
+        Sid::parse(sid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format(){
+        for sid in ["S-1-1-0", "S-1-5-18", "S-1-5-32-544", "S-1-5-21-3623811015-3361044348-30300820-1013", "S-1-0x123456789abc-1", "S-1-5"] {
+            assert_eq!(Sid::parse(sid).unwrap().to_string(), sid);
+        }
+
+        assert_eq!(Sid::parse("s-1-5-18").unwrap().to_string(), "S-1-5-18");
+        assert_eq!(Sid::parse("S-1-0x1000000000-7").unwrap().to_string(), "S-1-0x001000000000-7");
+        assert_eq!(Sid::parse("S-1-0x123456789ABC-1").unwrap().to_string(), "S-1-0x123456789abc-1");
+        assert_eq!(Sid::parse("S-1-0x5-18").unwrap(), Sid::new(5, &[18]).unwrap());
+        assert_eq!("S-1-5-32-545".parse::<Sid>().unwrap().sub_authorities(), [32, 545]);
+
+        for sid in ["", "S-1", "X-1-5", "S-2-5-18", "S-1-5-x", "S-1-5-4294967296", "S-1-5--1", "S-1-281474976710656", &format!("S-1-5{}", "-1".repeat(16))] {
+            assert!(matches!(Sid::parse(sid), Err(WinFileInfoError::UnparseableRecord { .. })), "{}", sid);
+        }
+    }
+
+    #[test]
+    fn test_binary_form(){
+        let sid = Sid::parse("S-1-5-32-544").unwrap();
+
+        assert_eq!(sid.to_bytes(), [1, 2, 0, 0, 0, 0, 0, 5, 32, 0, 0, 0, 0x20, 0x02, 0, 0]);
+        assert_eq!(Sid::from_bytes(&sid.to_bytes()).unwrap(), sid);
+
+        // with the rest of a security descriptor after it:
+        let mut bytes = Sid::parse("S-1-5-21-3623811015-3361044348-30300820-1013").unwrap().to_bytes();
+        bytes.extend_from_slice(&[0xFF, 0xFF]);
+
+        assert_eq!(Sid::from_bytes(&bytes).unwrap().to_string(), "S-1-5-21-3623811015-3361044348-30300820-1013");
+        assert!(Sid::from_bytes(&[1, 2, 0, 0, 0, 0, 0, 5, 32, 0, 0, 0]).is_err());
+        assert!(Sid::from_bytes(&[2, 0, 0, 0, 0, 0, 0, 5]).is_err());
+        assert!(Sid::from_bytes(&[1, 16, 0, 0, 0, 0, 0, 5]).is_err());
+    }
+
+    #[test]
+    fn test_domain_and_rid(){
+        let user = Sid::parse("S-1-5-21-3623811015-3361044348-30300820-1013").unwrap();
+        let administrators = Sid::parse("S-1-5-32-544").unwrap();
+
+        assert!(user.is_domain_account());
+        assert_eq!(user.rid(), Some(1013));
+        assert_eq!(user.domain().unwrap().to_string(), "S-1-5-21-3623811015-3361044348-30300820");
+        assert_eq!(administrators.rid(), Some(544));
+        assert_eq!(administrators.domain().unwrap().to_string(), "S-1-5-32");
+        assert_eq!(Sid::parse("S-1-5-18").unwrap().rid(), None);
+        assert_eq!(Sid::parse("S-1-5-18").unwrap().domain(), None);
+    }
+
+    #[test]
+    fn test_well_known(){
+        let name = |sid: &str| Sid::parse(sid).unwrap().well_known_name();
+
+        assert_eq!(name("S-1-1-0"), Some("Everyone"));
+        assert_eq!(name("S-1-5-18"), Some("NT AUTHORITY\\SYSTEM"));
+        assert_eq!(name("S-1-5-32-544"), Some("BUILTIN\\Administrators"));
+        assert_eq!(name("S-1-5-32-545"), Some("BUILTIN\\Users"));
+        assert_eq!(name("S-1-5-80-956008885-3418522649-1831038044-1853292631-2271478464"), Some("NT SERVICE\\TrustedInstaller"));
+        assert_eq!(name("S-1-15-3-1"), Some("APPLICATION PACKAGE AUTHORITY\\Your Internet connection"));
+        assert_eq!(name("S-1-5-21-1004336348-1177238915-682003330-512"), Some("Domain Admins"));
+        assert_eq!(name("S-1-5-21-1004336348-1177238915-682003330-1104"), None);
+
+        assert!(Sid::parse("S-1-5-80-956008885-3418522649-1831038044-1853292631-2271478464").unwrap().is_service());
+        assert!(Sid::parse("S-1-15-3-1024-1065365936-1281604716-3511738428-1654721687-432734479-3232135806-4053264122-3456934681").unwrap().is_capability());
+        assert_eq!(Sid::from_well_known_name("nt authority\\system").unwrap().to_string(), "S-1-5-18");
+        assert_eq!(Sid::from_well_known_name("Domain Admins"), None);
+        assert_eq!(Sid::from_sddl_alias("BA").unwrap().to_string(), "S-1-5-32-544");
+        assert_eq!(Sid::from_sddl_alias("DA"), None);
+        assert_eq!(Sid::parse("S-1-1-0").unwrap().sddl_alias(), Some("WD"));
+    }
+}
@@ -0,0 +1,383 @@
+use std::{cmp::Ordering, fmt, str::FromStr, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use crate::error::WinFileInfoError;
+
+const TICKS_PER_SECOND: i64 = 10_000_000;
+
+// seconds between 1601-01-01, which is the start of the FILETIME, and 1970-01-01.
+const SECONDS_FROM_1601_TO_1970: i64 = 11_644_473_600;
+
+const MAX_FILETIME: u64 = i64::MAX as u64;
+
+// a point in time with the precision of windows' FILETIME, 100 nanosecond ticks since 1601-01-01 UTC. it also
+// keeps the utc offset of the machine that it came from if the source has it, timestamps which are parsed from
+// the culture specific formats of the older versions has no offset and their ticks are their wall-clock time.
+//
+// timestamps are ordered by their ticks, so they can be sorted and compared directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowsTimestamp {
+    filetime: u64,
+    offset_minutes: Option<i16>
+}
+
+impl WindowsTimestamp {
+    // the filetimes from 2^63 on are clamped to the last one windows accepts, "FileTimeToSystemTime" rejects them.
+    pub fn from_filetime(filetime: u64, offset_minutes: Option<i16>) -> WindowsTimestamp {
+        WindowsTimestamp { filetime: filetime.min(MAX_FILETIME), offset_minutes }
+    }
+
+    pub fn from_system_time(time: SystemTime) -> WindowsTimestamp {
+        let ticks = match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => SECONDS_FROM_1601_TO_1970 as i128 * TICKS_PER_SECOND as i128 + duration.as_nanos() as i128 / 100,
+            Err(error) => SECONDS_FROM_1601_TO_1970 as i128 * TICKS_PER_SECOND as i128 - error.duration().as_nanos() as i128 / 100
+        };
+
+        WindowsTimestamp { filetime: ticks.clamp(0, MAX_FILETIME as i128) as u64, offset_minutes: Some(0) }
+    }
+
+    // 100 nanosecond ticks since 1601-01-01 UTC.
+    pub fn filetime(&self) -> u64 {
+        self.filetime
+    }
+
+    // utc offset of the source as minutes, "None" when the source didn't say it.
+    pub fn offset_minutes(&self) -> Option<i16> {
+        self.offset_minutes
+    }
+
+    // "None" when the platform's "SystemTime" can't hold it, like the far future ones on 32-bit targets.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let ticks_since_1970 = self.filetime as i64 - SECONDS_FROM_1601_TO_1970 * TICKS_PER_SECOND;
+        let ticks = ticks_since_1970.unsigned_abs();
+        let duration = Duration::new(ticks / TICKS_PER_SECOND as u64, (ticks % TICKS_PER_SECOND as u64) as u32 * 100);
+
+        match ticks_since_1970 >= 0 {
+            true => UNIX_EPOCH.checked_add(duration),
+            false => UNIX_EPOCH.checked_sub(duration)
+        }
+    }
+
+    // parses the round-trip format ("2026-10-18T14:02:11.1234567+03:00") that the queries request, the
+    // "/Date(1760785331000)/" format of "ConvertTo-Json" and the culture specific formats that the older
+    // versions of that crate returned, like "18.10.2026 14:02:11", "10/18/2026 2:02:11 PM" and "2026/10/18 14:02:11".
+    pub fn parse(input: &str) -> Result<WindowsTimestamp, WinFileInfoError> {
+        let input = input.trim();
+
+        if let Some(json_date) = input.strip_prefix("/Date(").and_then(|rest| rest.strip_suffix(")/")) {
+            return parse_json_date(json_date).ok_or_else(|| invalid_timestamp(input));
+        }
+
+        parse_date_and_time(input).ok_or_else(|| invalid_timestamp(input))
+    }
+
+    fn local_ticks(&self) -> i64 {
+        (self.filetime as i64).saturating_add(self.offset_minutes.unwrap_or(0) as i64 * 60 * TICKS_PER_SECOND)
+    }
+}
+
+impl PartialOrd for WindowsTimestamp {
+    fn partial_cmp(&self, other: &WindowsTimestamp) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WindowsTimestamp {
+    fn cmp(&self, other: &WindowsTimestamp) -> Ordering {
+        self.filetime.cmp(&other.filetime).then(self.offset_minutes.cmp(&other.offset_minutes))
+    }
+}
+
+// writes the timestamp in round-trip format with its own offset, which "WindowsTimestamp::parse()" reads back.
+impl fmt::Display for WindowsTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let local_ticks = self.local_ticks() - SECONDS_FROM_1601_TO_1970 * TICKS_PER_SECOND;
+        let seconds = local_ticks.div_euclid(TICKS_PER_SECOND);
+        let fraction = local_ticks.rem_euclid(TICKS_PER_SECOND);
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let second_of_day = seconds.rem_euclid(86_400);
+
+        write!(f, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:07}", year, month, day, second_of_day / 3600, second_of_day % 3600 / 60, second_of_day % 60, fraction)?;
+
+        match self.offset_minutes {
+            None => Ok(()),
+            Some(0) => write!(f, "Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+
+                write!(f, "{}{:02}:{:02}", sign, offset.unsigned_abs() / 60, offset.unsigned_abs() % 60)
+            }
+        }
+    }
+}
+
+impl FromStr for WindowsTimestamp {
+    type Err = WinFileInfoError;
+
+    fn from_str(input: &str) -> Result<WindowsTimestamp, WinFileInfoError> {
+        WindowsTimestamp::parse(input)
+    }
+}
+
+fn invalid_timestamp(input: &str) -> WinFileInfoError {
+    WinFileInfoError::unparseable(input, "it's not a timestamp that we can read")
+}
+
+// days since 1970-01-01 of the given date, from Howard Hinnant's "chrono-compatible low-level date algorithms".
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+
+    (year_of_era + era * 400 + if month <= 2 { 1 } else { 0 }, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    }
+}
+
+fn timestamp_from_parts(date: (i64, i64, i64), time: (i64, i64, i64, i64), offset_minutes: Option<i16>) -> Option<WindowsTimestamp> {
+    let (year, month, day) = date;
+    let (hour, minute, second, fraction) = time;
+
+    if !(1601..=30827).contains(&year) || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second + SECONDS_FROM_1601_TO_1970;
+    let ticks = seconds * TICKS_PER_SECOND + fraction - offset_minutes.unwrap_or(0) as i64 * 60 * TICKS_PER_SECOND;
+
+    u64::try_from(ticks).ok().map(|filetime| WindowsTimestamp { filetime, offset_minutes })
+}
+
+// "1760785331000" or "1760785331000+0300", milliseconds since 1970-01-01 UTC with an optional offset.
+fn parse_json_date(input: &str) -> Option<WindowsTimestamp> {
+    let sign_position = input.rfind(['+', '-']).filter(|position| *position > 0);
+
+    let (milliseconds, offset_minutes) = match sign_position {
+        Some(position) => {
+            let offset = &input[position + 1..];
+
+            if offset.len() != 4 {
+                return None;
+            }
+
+            let minutes = offset[..2].parse::<i16>().ok()? * 60 + offset[2..].parse::<i16>().ok()?;
+
+            (&input[..position], Some(if input[position..].starts_with('-') { -minutes } else { minutes }))
+        },
+        None => (input, Some(0))
+    };
+
+    let ticks = milliseconds.parse::<i64>().ok()?.checked_mul(10_000)?.checked_add(SECONDS_FROM_1601_TO_1970 * TICKS_PER_SECOND)?;
+
+    u64::try_from(ticks).ok().map(|filetime| WindowsTimestamp { filetime, offset_minutes })
+}
+
+fn parse_number(input: &str) -> Option<i64> {
+    if input.is_empty() || !input.chars().all(|character| character.is_ascii_digit()) {
+        return None;
+    }
+
+    input.parse().ok()
+}
+
+fn parse_date_and_time(input: &str) -> Option<WindowsTimestamp> {
+    let (date, time) = match input.find(['T', ' ']) {
+        Some(position) => (&input[..position], input[position + 1..].trim()),
+        None => return None
+    };
+
+    // "AM"/"PM" after the time in the 12 hour cultures, "上午"/"下午" before it in zh-TW:
+    let (time, meridiem) = match time.split_whitespace().collect::<Vec<&str>>()[..] {
+        [time] => (time, None),
+        [time, meridiem] | [meridiem, time] if meridiem.eq_ignore_ascii_case("AM") || meridiem == "上午" => (time, Some(false)),
+        [time, meridiem] | [meridiem, time] if meridiem.eq_ignore_ascii_case("PM") || meridiem == "下午" => (time, Some(true)),
+        _ => return None
+    };
+
+    let (time, offset_minutes) = split_offset(time)?;
+
+    let date_parts: Vec<&str> = date.split(['-', '.', '/']).collect();
+
+    if date_parts.len() != 3 {
+        return None;
+    }
+
+    let first = parse_number(date_parts[0])?;
+    let second = parse_number(date_parts[1])?;
+    let third = parse_number(date_parts[2])?;
+
+    // "2026-10-18", and "2026/10/18" of ja-JP, zh-CN and zh-TW:
+    let date = if date_parts[0].len() == 4 {
+        (first, second, third)
+    } else if date.contains('.') || date.contains('-') {
+        (third, second, first)
+    } else if (meridiem.is_some() && first <= 12) || second > 12 {
+        // "10/18/2026 2:02:11 PM" of en-US
+        (third, first, second)
+    } else {
+        // "18/10/2026 14:02:11" of en-GB, fr-FR and many others
+        (third, second, first)
+    };
+
+    let time_parts: Vec<&str> = time.split(':').collect();
+
+    if time_parts.len() != 3 {
+        return None;
+    }
+
+    let (seconds, fraction) = match time_parts[2].split_once(['.', ',']) {
+        Some((seconds, fraction)) if !fraction.is_empty() && fraction.len() <= 7 => (seconds, parse_number(fraction)? * 10_i64.pow(7 - fraction.len() as u32)),
+        Some(_) => return None,
+        None => (time_parts[2], 0)
+    };
+
+    let mut hour = parse_number(time_parts[0])?;
+    let minute = parse_number(time_parts[1])?;
+    let second = parse_number(seconds)?;
+
+    match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(false) if hour == 12 => hour = 0,
+        Some(true) if hour != 12 => hour += 12,
+        _ => ()
+    }
+
+    timestamp_from_parts(date, (hour, minute, second, fraction), offset_minutes)
+}
+
+// splits "14:02:11.1234567+03:00" into the time and offset parts, "Z" is utc and no suffix is no offset.
+fn split_offset(time: &str) -> Option<(&str, Option<i16>)> {
+    if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        return Some((time, Some(0)));
+    }
+
+    match time.rfind(['+', '-']) {
+        Some(position) => {
+            let (hours, minutes) = time[position + 1..].split_once(':')?;
+            let minutes = parse_number(hours)? * 60 + parse_number(minutes)?;
+
+            if minutes > 14 * 60 {
+                return None;
+            }
+
+            let minutes = if time[position..].starts_with('-') { -minutes } else { minutes };
+
+            Some((&time[..position], Some(minutes as i16)))
+        },
+        None => Some((time, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CULTURE_TIMESTAMPS: &str = include_str!("../tests/fixtures/culture_timestamps.txt");
+
+    #[test]
+    fn test_parse_round_trip_format(){
+        let timestamp = WindowsTimestamp::parse("2026-10-18T14:02:11.1234567+03:00").unwrap();
+
+        assert_eq!(timestamp.offset_minutes(), Some(180));
+        assert_eq!(timestamp.filetime(), WindowsTimestamp::parse("2026-10-18T11:02:11.1234567Z").unwrap().filetime());
+        assert_eq!(timestamp.to_string(), "2026-10-18T14:02:11.1234567+03:00");
+        assert_eq!(timestamp.filetime() % TICKS_PER_SECOND as u64, 1_234_567);
+    }
+
+    #[test]
+    fn test_parse_culture_specific_formats(){
+        let expected = WindowsTimestamp::parse("2026-10-18T14:02:11").unwrap();
+
+        assert_eq!(WindowsTimestamp::parse("18.10.2026 14:02:11").unwrap(), expected);
+        assert_eq!(WindowsTimestamp::parse("10/18/2026 2:02:11 PM").unwrap(), expected);
+        assert_eq!(WindowsTimestamp::parse("18/10/2026 14:02:11").unwrap(), expected);
+        assert_eq!(WindowsTimestamp::parse("2026-10-18 14:02:11").unwrap(), expected);
+        assert_eq!(WindowsTimestamp::parse("18-10-2026 14:02:11").unwrap(), expected);
+        assert_eq!(WindowsTimestamp::parse("10/18/2026 12:02:11 AM").unwrap().to_string(), "2026-10-18T00:02:11.0000000");
+        assert_eq!(expected.offset_minutes(), None);
+
+        // "<culture> <timestamp>" lines, all of them 2026-10-18 14:02:11:
+        for line in CULTURE_TIMESTAMPS.lines().filter(|line| !line.is_empty()) {
+            let (culture, timestamp) = line.split_once(' ').unwrap();
+
+            assert_eq!(WindowsTimestamp::parse(timestamp).ok(), Some(expected), "{} of {}", timestamp, culture);
+        }
+    }
+
+    #[test]
+    fn test_parse_json_date(){
+        let timestamp = WindowsTimestamp::parse("/Date(1760796131000)/").unwrap();
+
+        assert_eq!(timestamp.to_system_time(), Some(UNIX_EPOCH + Duration::from_secs(1_760_796_131)));
+        assert_eq!(timestamp.to_string(), "2025-10-18T14:02:11.0000000Z");
+        assert_eq!(WindowsTimestamp::parse("/Date(1760796131000+0300)/").unwrap().offset_minutes(), Some(180));
+    }
+
+    #[test]
+    fn test_system_time_conversion(){
+        let time = UNIX_EPOCH + Duration::from_nanos(1_760_796_131_123_456_700);
+        let timestamp = WindowsTimestamp::from_system_time(time);
+
+        assert_eq!(timestamp.to_system_time(), Some(time));
+        assert_eq!(WindowsTimestamp::from_filetime(0, Some(0)).to_string(), "1601-01-01T00:00:00.0000000Z");
+        assert_eq!(WindowsTimestamp::from_filetime(0, Some(0)).to_system_time(), Some(UNIX_EPOCH - Duration::from_secs(11_644_473_600)));
+
+        // the ticks of the far future don't fit into the nanoseconds of a "u64":
+        let last = WindowsTimestamp::parse("9999-12-31T23:59:59.9999999Z").unwrap();
+
+        assert_eq!(last.to_system_time(), Some(UNIX_EPOCH + Duration::new(253_402_300_799, 999_999_900)));
+
+        // the filetimes that would be negative as an "i64":
+        let clamped = WindowsTimestamp::from_filetime(u64::MAX, Some(0));
+
+        assert_eq!(clamped.filetime(), i64::MAX as u64);
+        assert_eq!(clamped.to_string(), "30828-09-14T02:48:05.4775807Z");
+        assert!(clamped.to_system_time().is_some());
+    }
+
+    #[test]
+    fn test_ordering(){
+        let mut timestamps = [
+            WindowsTimestamp::parse("2026-10-18T14:02:11+03:00").unwrap(),
+            WindowsTimestamp::parse("2026-10-18T10:02:11Z").unwrap(),
+            WindowsTimestamp::parse("2024-02-29T23:59:59.9999999Z").unwrap()
+        ];
+
+        timestamps.sort();
+
+        assert_eq!(timestamps[0].to_string(), "2024-02-29T23:59:59.9999999Z");
+        assert_eq!(timestamps[2].to_string(), "2026-10-18T14:02:11.0000000+03:00");
+    }
+
+    #[test]
+    fn test_parse_errors(){
+        for input in ["", "yesterday", "31.02.2026 10:00:00", "2026-10-18T25:00:00", "13/13/2026 10:00:00", "2026-10-18T10:00:00+15:00", "10/18/2026 13:02:11 PM", "2026-10-18T10:00:00.12345678", "2026/18/10 14:02:11", "2026/10/18 下午 14:02:11", "2026/10/18 2:02:11 PM AM", "/Date(99999999999999999)/"] {
+            assert!(WindowsTimestamp::parse(input).is_err(), "{} parsed", input);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::path::{PathPrefix, WindowsPath};
+
+// posix path forms of the windows drives, the ones which the tools running under them give us.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PosixStyle {
+    // "/mnt/c/Users", and "\\wsl.localhost\<distro>\home" for the files inside of the distro.
+    Wsl,
+    // "/c/Users", git bash and msys2.
+    Msys,
+    // "/cygdrive/c/Users"
+    Cygwin
+}
+
+// "c/Users/necoo" to "C:\Users\necoo", "None" if it doesn't start with a single drive letter.
+fn drive_path(rest: &str) -> Option<String> {
+    let (drive, components) = match rest.split_once('/') {
+        Some((drive, components)) => (drive, components),
+        None => (rest, "")
+    };
+
+    let mut chars = drive.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_alphabetic() => {
+            let components: Vec<&str> = components.split('/').filter(|component| !component.is_empty()).collect();
+
+            Some(format!("{}:\\{}", letter.to_ascii_uppercase(), components.join("\\")))
+        },
+        _ => None
+    }
+}
+
+// translates wsl ("/mnt/c/..."), cygwin ("/cygdrive/c/...") and msys ("/c/...") paths of the windows drives to
+// windows paths. "None" for the other paths, which have no windows form without knowing more, like "/home/necoo".
+//
+// let path = posix_to_windows("/mnt/c/Users/necoo/Desktop").unwrap();
+//
+// assert_eq!(path, "C:\\Users\\necoo\\Desktop");
+pub fn posix_to_windows(path: &str) -> Option<String> {
+    if let Some(rest) = path.strip_prefix("/mnt/") {
+        return drive_path(rest);
+    }
+
+    if let Some(rest) = path.strip_prefix("/cygdrive/") {
+        return drive_path(rest);
+    }
+
+    drive_path(path.strip_prefix('/')?)
+}
+
+// like "posix_to_windows()" for the wsl paths, but the paths which are not on a windows drive are translated to
+// the "\\wsl.localhost" share of given distro, where windows sees the files of the distro.
+pub fn wsl_to_windows(path: &str, distro: &str) -> String {
+    if let Some(windows_path) = path.strip_prefix("/mnt/").and_then(drive_path) {
+        return windows_path;
+    }
+
+    let components: Vec<&str> = path.split('/').filter(|component| !component.is_empty()).collect();
+
+    match path.starts_with('/') {
+        true => format!("\\\\wsl.localhost\\{}\\{}", distro, components.join("\\")).trim_end_matches('\\').to_string(),
+        false => components.join("\\")
+    }
+}
+
+// translates a windows path to given posix style. "\\wsl$\<distro>\..." and "\\wsl.localhost\<distro>\..." become
+// the paths inside of the distro for wsl, other unc paths stay "//server/share/..." for msys and cygwin. "None" for
+// the paths that style can't reach, like drive relative paths or unc shares from wsl.
+pub fn windows_to_posix(path: &str, style: PosixStyle) -> Option<String> {
+    let path = WindowsPath::parse(path).ok()?;
+    let path = path.to_win32().unwrap_or(path).normalize();
+    let components = path.components().join("/");
+
+    let root = match (path.prefix(), path.has_root()) {
+        (Some(PathPrefix::Disk(letter)), true) => match style {
+            PosixStyle::Wsl => format!("/mnt/{}", letter.to_ascii_lowercase()),
+            PosixStyle::Msys => format!("/{}", letter.to_ascii_lowercase()),
+            PosixStyle::Cygwin => format!("/cygdrive/{}", letter.to_ascii_lowercase())
+        },
+        (Some(PathPrefix::Unc { server, .. }), _) if style == PosixStyle::Wsl && (server.eq_ignore_ascii_case("wsl$") || server.eq_ignore_ascii_case("wsl.localhost")) => "".to_string(),
+        (Some(PathPrefix::Unc { server, share }), _) if style != PosixStyle::Wsl => format!("//{}/{}", server, share),
+        (None, false) => return Some(components),
+        _ => return None
+    };
+
+    match (root.is_empty(), components.is_empty()) {
+        (true, _) => Some(format!("/{}", components)),
+        (false, true) => Some(root),
+        (false, false) => Some(format!("{}/{}", root, components))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_posix_to_windows(){
+        assert_eq!(posix_to_windows("/mnt/c/Users/necoo/Desktop").unwrap(), "C:\\Users\\necoo\\Desktop");
+        assert_eq!(posix_to_windows("/cygdrive/d/data/").unwrap(), "D:\\data");
+        assert_eq!(posix_to_windows("/c/Users//necoo").unwrap(), "C:\\Users\\necoo");
+        assert_eq!(posix_to_windows("/mnt/c").unwrap(), "C:\\");
+        assert_eq!(posix_to_windows("/home/necoo"), None);
+        assert_eq!(posix_to_windows("/mnt/wsl/x"), None);
+        assert_eq!(posix_to_windows("C:\\Users"), None);
+    }
+
+    #[test]
+    fn test_wsl_to_windows(){
+        assert_eq!(wsl_to_windows("/mnt/c/Users", "Ubuntu"), "C:\\Users");
+        assert_eq!(wsl_to_windows("/home/necoo/projects", "Ubuntu"), "\\\\wsl.localhost\\Ubuntu\\home\\necoo\\projects");
+        assert_eq!(wsl_to_windows("/", "Ubuntu"), "\\\\wsl.localhost\\Ubuntu");
+        assert_eq!(wsl_to_windows("projects/a.txt", "Ubuntu"), "projects\\a.txt");
+    }
+
+    #[test]
+    fn test_windows_to_posix(){
+        assert_eq!(windows_to_posix("C:\\Users\\necoo", PosixStyle::Wsl).unwrap(), "/mnt/c/Users/necoo");
+        assert_eq!(windows_to_posix("C:\\Users\\necoo", PosixStyle::Msys).unwrap(), "/c/Users/necoo");
+        assert_eq!(windows_to_posix("D:\\", PosixStyle::Cygwin).unwrap(), "/cygdrive/d");
+        assert_eq!(windows_to_posix("\\\\?\\C:\\Users", PosixStyle::Wsl).unwrap(), "/mnt/c/Users");
+        assert_eq!(windows_to_posix("\\\\wsl$\\Ubuntu\\home\\necoo", PosixStyle::Wsl).unwrap(), "/home/necoo");
+        assert_eq!(windows_to_posix("\\\\wsl.localhost\\Ubuntu", PosixStyle::Wsl).unwrap(), "/");
+        assert_eq!(windows_to_posix("\\\\server\\share\\docs", PosixStyle::Msys).unwrap(), "//server/share/docs");
+        assert_eq!(windows_to_posix("\\\\server\\share\\docs", PosixStyle::Wsl), None);
+        assert_eq!(windows_to_posix("C:Users", PosixStyle::Wsl), None);
+        assert_eq!(windows_to_posix("docs\\a.txt", PosixStyle::Cygwin).unwrap(), "docs/a.txt");
+    }
+
+    #[test]
+    fn test_round_trip(){
+        for (path, style) in [("/mnt/c/Users/necoo", PosixStyle::Wsl), ("/c/Program Files/Git", PosixStyle::Msys), ("/cygdrive/e/çalışma", PosixStyle::Cygwin)] {
+            assert_eq!(windows_to_posix(&posix_to_windows(path).unwrap(), style).unwrap(), path);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::fmt;
+
+use crate::path::WindowsPath;
+
+// a component can be 255 utf-16 units long on ntfs, fat and smb shares.
+pub const MAX_COMPONENT_LENGTH: usize = 255;
+
+// "MAX_PATH" of win32 counts the terminating null too, so a path can have 259 characters.
+pub const MAX_PATH: usize = 260;
+
+// limit of the verbatim paths and the long path mode.
+pub const MAX_LONG_PATH: usize = 32767;
+
+// names which open a device instead of a file, with or without an extension. superscript digits count as digits
+// for windows too.
+const RESERVED_NAMES: [&str; 30] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM0", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "COM\u{B9}", "COM\u{B2}", "COM\u{B3}",
+    "LPT0", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9", "LPT\u{B9}", "LPT\u{B2}", "LPT\u{B3}"
+];
+
+const FORBIDDEN_CHARACTERS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+// something that makes a name or path invalid on windows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameProblem {
+    Empty,
+    // "CON", "nul.txt", "COM¹.log" and the like, it has the reserved part of the name.
+    ReservedName(String),
+    ForbiddenCharacter(char),
+    ControlCharacter(char),
+    // windows strips them silently, so "notes." and "notes" become the same file.
+    TrailingDotOrSpace,
+    ComponentTooLong { length: usize, limit: usize },
+    PathTooLong { length: usize, limit: usize }
+}
+
+impl fmt::Display for NameProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameProblem::Empty => write!(f, "name is empty"),
+            NameProblem::ReservedName(name) => write!(f, "'{}' is a reserved device name", name),
+            NameProblem::ForbiddenCharacter(character) => write!(f, "'{}' is not allowed in names", character),
+            NameProblem::ControlCharacter(character) => write!(f, "control character {:#04x} is not allowed in names", *character as u32),
+            NameProblem::TrailingDotOrSpace => write!(f, "name ends with a dot or space"),
+            NameProblem::ComponentTooLong { length, limit } => write!(f, "name is {} characters long, limit is {}", length, limit),
+            NameProblem::PathTooLong { length, limit } => write!(f, "path is {} characters long, limit is {}", length, limit)
+        }
+    }
+}
+
+// lengths are in utf-16 units, like windows counts them.
+fn windows_length(value: &str) -> usize {
+    value.encode_utf16().count()
+}
+
+// "CON", "con.txt" and "CON .tar.gz" are reserved, windows looks at the part before the first dot without its
+// trailing spaces.
+fn reserved_part(name: &str) -> Option<&str> {
+    let stem = name.split('.').next().unwrap_or("").trim_end_matches(' ');
+
+    match RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        true => Some(stem),
+        false => None
+    }
+}
+
+// checks a single component, like "notes.txt". every problem of the name is returned, in the order of the checks.
+pub fn validate_file_name(name: &str) -> Result<(), Vec<NameProblem>> {
+    if name.is_empty() {
+        return Err(vec![NameProblem::Empty]);
+    }
+
+    let mut problems = vec![];
+
+    if let Some(reserved) = reserved_part(name) {
+        problems.push(NameProblem::ReservedName(reserved.to_string()));
+    }
+
+    for character in name.chars() {
+        if FORBIDDEN_CHARACTERS.contains(&character) && !problems.contains(&NameProblem::ForbiddenCharacter(character)) {
+            problems.push(NameProblem::ForbiddenCharacter(character));
+        }
+
+        if character.is_ascii_control() && character != '\u{7F}' && !problems.contains(&NameProblem::ControlCharacter(character)) {
+            problems.push(NameProblem::ControlCharacter(character));
+        }
+    }
+
+    if name.ends_with(['.', ' ']) {
+        problems.push(NameProblem::TrailingDotOrSpace);
+    }
+
+    if windows_length(name) > MAX_COMPONENT_LENGTH {
+        problems.push(NameProblem::ComponentTooLong { length: windows_length(name), limit: MAX_COMPONENT_LENGTH });
+    }
+
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(problems)
+    }
+}
+
+// checks every component of the path and its whole length. without "long_paths" the path has to fit to
+// "MAX_PATH", unless it's a verbatim path, which windows never limits that way. "." and ".." are skipped, since
+// they are not names.
+pub fn validate_path(path: &WindowsPath, long_paths: bool) -> Result<(), Vec<NameProblem>> {
+    let mut problems = vec![];
+
+    for component in path.components().iter().filter(|component| *component != "." && *component != "..") {
+        if let Err(component_problems) = validate_file_name(component) {
+            problems.extend(component_problems);
+        }
+    }
+
+    let length = windows_length(&path.to_string());
+    let limit = match long_paths || path.is_verbatim() {
+        true => MAX_LONG_PATH,
+        false => MAX_PATH - 1
+    };
+
+    if length > limit {
+        problems.push(NameProblem::PathTooLong { length, limit });
+    }
+
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(problems)
+    }
+}
+
+// nearest valid name of given name, the same name always gives the same result:
+//
+// - forbidden and control characters become "_".
+// - names longer than the limit are cut from the end of their stem, so the extension stays.
+// - trailing dots and spaces are removed.
+// - reserved names get a "_" after their reserved part, like "CON.txt" to "CON_.txt".
+// - empty names become "_".
+pub fn sanitize_file_name(name: &str) -> String {
+    let replaced: String = name.chars().map(|character| match FORBIDDEN_CHARACTERS.contains(&character) || (character.is_ascii_control() && character != '\u{7F}') {
+        true => '_',
+        false => character
+    }).collect();
+
+    let mut sanitized = escape_reserved(replaced);
+    sanitized = truncate_keeping_extension(&sanitized, MAX_COMPONENT_LENGTH).trim_end_matches(['.', ' ']).to_string();
+
+    match sanitized.is_empty() {
+        true => "_".to_string(),
+        false => escape_reserved(sanitized)
+    }
+}
+
+// the "_" goes right after the reserved part, so the name doesn't get longer than it has to be when it's cut.
+fn escape_reserved(mut name: String) -> String {
+    if let Some(reserved) = reserved_part(&name) {
+        name.insert(reserved.len(), '_');
+    }
+
+    name
+}
+
+fn truncate_keeping_extension(name: &str, limit: usize) -> String {
+    if windows_length(name) <= limit {
+        return name.to_string();
+    }
+
+    // very long extensions are not worth keeping, the name is cut as a whole then.
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() && windows_length(extension) < limit / 2 => (stem, format!(".{}", extension)),
+        _ => (name, "".to_string())
+    };
+
+    let mut length = windows_length(&extension);
+    let mut truncated = String::new();
+
+    for character in stem.chars() {
+        if length + character.len_utf16() > limit {
+            break;
+        }
+
+        length += character.len_utf16();
+        truncated.push(character);
+    }
+
+    truncated + &extension
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserved_names(){
+        for name in ["CON", "con", "nul.txt", "NUL.tar.gz", "COM1", "lpt9.log", "COM\u{B9}", "lpt\u{B3}.txt", "AUX ", "PRN .txt"] {
+            assert!(matches!(validate_file_name(name).unwrap_err()[0], NameProblem::ReservedName(_)), "{}", name);
+        }
+
+        for name in ["CONSOLE", "COM10", "LPT", "nul_", "my con.txt", "COM1x"] {
+            assert_eq!(validate_file_name(name), Ok(()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_forbidden_characters(){
+        assert_eq!(validate_file_name("a<b>c"), Err(vec![NameProblem::ForbiddenCharacter('<'), NameProblem::ForbiddenCharacter('>')]));
+        assert_eq!(validate_file_name("a\u{1}b"), Err(vec![NameProblem::ControlCharacter('\u{1}')]));
+        assert_eq!(validate_file_name("notes."), Err(vec![NameProblem::TrailingDotOrSpace]));
+        assert_eq!(validate_file_name("notes "), Err(vec![NameProblem::TrailingDotOrSpace]));
+        assert_eq!(validate_file_name(""), Err(vec![NameProblem::Empty]));
+        assert_eq!(validate_file_name("report : final 日本語 🙂.txt"), Err(vec![NameProblem::ForbiddenCharacter(':')]));
+        assert_eq!(validate_file_name("çalışma planı.xlsx"), Ok(()));
+    }
+
+    #[test]
+    fn test_lengths(){
+        assert_eq!(validate_file_name(&"a".repeat(255)), Ok(()));
+        assert_eq!(validate_file_name(&"a".repeat(256)), Err(vec![NameProblem::ComponentTooLong { length: 256, limit: 255 }]));
+        // "🙂" is two utf-16 units:
+        assert!(validate_file_name(&"🙂".repeat(128)).is_err());
+
+        let long_path = WindowsPath::parse(&format!("C:\\{}\\{}", "a".repeat(200), "b".repeat(100))).unwrap();
+
+        assert_eq!(validate_path(&long_path, false), Err(vec![NameProblem::PathTooLong { length: 304, limit: 259 }]));
+        assert_eq!(validate_path(&long_path, true), Ok(()));
+        assert_eq!(validate_path(&long_path.to_verbatim().unwrap(), false), Ok(()));
+        assert_eq!(validate_path(&WindowsPath::parse("C:\\Users\\..\\CON\\a?.txt").unwrap(), false), Err(vec![NameProblem::ReservedName("CON".to_string()), NameProblem::ForbiddenCharacter('?')]));
+    }
+
+    #[test]
+    fn test_sanitize_file_name(){
+        assert_eq!(sanitize_file_name("a<b>.txt"), "a_b_.txt");
+        assert_eq!(sanitize_file_name("CON.txt"), "CON_.txt");
+        assert_eq!(sanitize_file_name("nul"), "nul_");
+        assert_eq!(sanitize_file_name("COM1 .log"), "COM1_ .log");
+        assert_eq!(sanitize_file_name("notes. . "), "notes");
+        assert_eq!(sanitize_file_name("..."), "_");
+        assert_eq!(sanitize_file_name(""), "_");
+        assert_eq!(sanitize_file_name("report : final.txt"), "report _ final.txt");
+
+        let long_name = format!("{}.docx", "x".repeat(300));
+        let sanitized = sanitize_file_name(&long_name);
+
+        assert_eq!(sanitized.len(), 255);
+        assert!(sanitized.ends_with("x.docx"));
+    }
+
+    #[test]
+    fn test_sanitized_names_are_valid(){
+        let samples = [
+            "".to_string(), " ".to_string(), ".".to_string(), "..".to_string(), "CON".to_string(), "con.".to_string(), "a\u{0}b".to_string(), "*?".to_string(),
+            "aux.txt.".to_string(), "LPT\u{B2} ...".to_string(), "x".repeat(300) + ". ", "🙂".repeat(200), "a".repeat(254) + "<>", "CON.".to_string() + &"x".repeat(251)
+        ];
+
+        for sample in samples {
+            let sanitized = sanitize_file_name(&sample);
+
+            assert_eq!(validate_file_name(&sanitized), Ok(()), "{:?} became {:?}", sample, sanitized);
+            assert_eq!(sanitize_file_name(&sanitized), sanitized);
+        }
+    }
+}
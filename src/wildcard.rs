@@ -0,0 +1,204 @@
+use crate::{key::upcase, short_name::generate_short_name};
+
+// the dos wildcards of the file system, "FindFirstFile" turns the "*", "?" and "." of a filter to them.
+pub const DOS_STAR: char = '<';
+pub const DOS_QM: char = '>';
+pub const DOS_DOT: char = '"';
+
+// matches "name" with "expression" like "FsRtlIsNameInExpression" of windows does:
+//
+// - "*" matches any number of characters and "?" matches a single one.
+// - "<" matches any number of characters, except the last "." of the name.
+// - ">" matches a single character, or nothing at a "." or at the end of the name.
+// - "\"" matches a ".", or nothing at the end of the name.
+//
+// every other character matches itself, upper-cased like ntfs does when "ignore_case" is on. the characters are
+// utf-16 units like in windows, so a "?" doesn't match an emoji but "??" does.
+pub fn is_name_in_expression(expression: &str, name: &str, ignore_case: bool) -> bool {
+    let fold = |unit: u16| match ignore_case {
+        true => upcase(unit),
+        false => unit
+    };
+
+    let expression: Vec<u16> = expression.encode_utf16().map(fold).collect();
+    let name: Vec<u16> = name.encode_utf16().map(fold).collect();
+
+    matches_units(&expression, &name)
+}
+
+fn matches_units(expression: &[u16], name: &[u16]) -> bool {
+    let (star, question, dos_star, dos_qm, dos_dot, dot) = ('*' as u16, '?' as u16, DOS_STAR as u16, DOS_QM as u16, DOS_DOT as u16, '.' as u16);
+    let last_dot = name.iter().rposition(|unit| *unit == dot);
+
+    // "matches[e][n]" is true when "expression[e..]" matches "name[n..]", it's filled from the ends.
+    let width = name.len() + 1;
+    let mut matches = vec![false; (expression.len() + 1) * width];
+    matches[expression.len() * width + name.len()] = true;
+
+    for e in (0..expression.len()).rev() {
+        for n in (0..=name.len()).rev() {
+            let next = |e: usize, n: usize| matches[e * width + n];
+            let at_end = n == name.len();
+
+            matches[e * width + n] = match expression[e] {
+                unit if unit == star => next(e + 1, n) || (!at_end && next(e, n + 1)),
+                unit if unit == question => !at_end && next(e + 1, n + 1),
+                unit if unit == dos_star => next(e + 1, n) || (!at_end && last_dot != Some(n) && next(e, n + 1)),
+                unit if unit == dos_qm => match at_end || name[n] == dot {
+                    true => next(e + 1, n),
+                    false => next(e + 1, n + 1)
+                },
+                unit if unit == dos_dot => match at_end {
+                    true => next(e + 1, n),
+                    false => name[n] == dot && next(e + 1, n + 1)
+                },
+                unit => !at_end && name[n] == unit && next(e + 1, n + 1)
+            };
+        }
+    }
+
+    matches[0]
+}
+
+// the "-Filter" of "Get-ChildItem", which goes to "FindFirstFile" and from there to the file system:
+//
+// let filter = WildcardFilter::new("*.htm");
+//
+// assert!(filter.matches("index.htm"));
+// assert!(!filter.matches("index.html"));
+// assert!(filter.with_short_names(true).matches("index.html"));
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WildcardFilter {
+    // the filter after the translation of "FindFirstFile", in dos wildcards.
+    pub expression: String,
+    // windows matches the 8.3 short names of the entities too, when their volume has them. that's why "*.htm"
+    // finds "index.html", its short name is "INDEX~1.HTM".
+    pub short_names: bool
+}
+
+impl WildcardFilter {
+    pub fn new(filter: &str) -> WildcardFilter {
+        WildcardFilter { expression: dos_expression(filter), short_names: false }
+    }
+
+    pub fn with_short_names(mut self, short_names: bool) -> WildcardFilter {
+        self.short_names = short_names;
+
+        self
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.matches_names(name, None)
+    }
+
+    // "short_name" is the 8.3 alias of the entity if it has one. when "short_names" is on and it's not given, the
+    // alias that windows would give to a lonely entity with this name is matched.
+    pub fn matches_names(&self, name: &str, short_name: Option<&str>) -> bool {
+        if is_name_in_expression(&self.expression, name, true) {
+            return true;
+        }
+
+        if !self.short_names {
+            return false;
+        }
+
+        match short_name {
+            Some(short_name) => is_name_in_expression(&self.expression, short_name, true),
+            None => generate_short_name(name, &[]).is_some_and(|short_name| is_name_in_expression(&self.expression, &short_name, true))
+        }
+    }
+}
+
+// the translation that "FindFirstFile" does before asking the file system, so "*.*" matches the names without a
+// dot and "*." matches only them:
+//
+// - "*.*" as the whole filter becomes "*".
+// - "?" becomes ">".
+// - "*" before a "." becomes "<".
+// - "." before a "?", a "*" or the end of the filter becomes "\"".
+pub fn dos_expression(filter: &str) -> String {
+    if filter == "*.*" {
+        return "*".to_string();
+    }
+
+    let characters: Vec<char> = filter.chars().collect();
+
+    characters.iter().enumerate().map(|(index, character)| match (character, characters.get(index + 1)) {
+        ('?', _) => DOS_QM,
+        ('*', Some('.')) => DOS_STAR,
+        ('.', Some('?')) | ('.', Some('*')) | ('.', None) => DOS_DOT,
+        (character, _) => *character
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_star_and_question_mark(){
+        assert!(is_name_in_expression("*.TXT", "notes.txt", true));
+        assert!(!is_name_in_expression("*.TXT", "notes.txt", false));
+        assert!(is_name_in_expression("n?tes*", "notes.txt", true));
+        assert!(is_name_in_expression("*", "", true));
+        assert!(!is_name_in_expression("*.txt", "notes.txt.bak", true));
+        assert!(!is_name_in_expression("a*b*c", "abx", true));
+        assert!(is_name_in_expression("ÇALIŞMA*", "çalişma planı.xlsx", true));
+        // an emoji is two utf-16 units:
+        assert!(!is_name_in_expression("?", "🙂", true));
+        assert!(is_name_in_expression("??", "🙂", true));
+    }
+
+    #[test]
+    fn test_dos_wildcards(){
+        // "<" stops at the last dot:
+        assert!(is_name_in_expression("<.txt", "a.b.txt", true));
+        assert!(is_name_in_expression("<", "notes", true));
+        assert!(!is_name_in_expression("<", "notes.txt", true));
+        // ">" matches nothing at a dot or at the end:
+        assert!(is_name_in_expression("a>>.txt", "a.txt", true));
+        assert!(is_name_in_expression("a>>.txt", "ab.txt", true));
+        assert!(!is_name_in_expression("a>>.txt", "abcd.txt", true));
+        assert!(is_name_in_expression("a>", "a", true));
+        // "\"" matches a dot or the end:
+        assert!(is_name_in_expression("a\"", "a", true));
+        assert!(is_name_in_expression("a\"", "a.", true));
+        assert!(!is_name_in_expression("a\"b", "ab", true));
+    }
+
+    #[test]
+    fn test_filters(){
+        assert_eq!(dos_expression("*.*"), "*");
+        assert_eq!(dos_expression("*.txt"), "<.txt");
+        assert_eq!(dos_expression("file?.*"), "file>\"*");
+        assert_eq!(dos_expression("*."), "<\"");
+
+        // "*.*" matches the names without a dot, "*." matches only them:
+        assert!(WildcardFilter::new("*.*").matches("Makefile"));
+        assert!(WildcardFilter::new("*.").matches("Makefile"));
+        assert!(!WildcardFilter::new("*.").matches("Cargo.toml"));
+        // "?" before a dot matches nothing too:
+        assert!(WildcardFilter::new("file??.txt").matches("file1.txt"));
+        assert!(!WildcardFilter::new("file?.txt").matches("file12.txt"));
+        assert!(WildcardFilter::new("*.?").matches("notes"));
+        assert!(WildcardFilter::new("*.tx?").matches("notes.tx"));
+        assert!(WildcardFilter::new("[a].txt").matches("[A].TXT"));
+    }
+
+    #[test]
+    fn test_short_names(){
+        let filter = WildcardFilter::new("*.htm");
+
+        assert!(!filter.matches("index.html"));
+        assert!(!filter.matches_names("index.html", Some("INDEX~1.HTM")));
+        assert!(filter.clone().with_short_names(true).matches_names("index.html", Some("INDEX~2.HTM")));
+        assert!(!filter.clone().with_short_names(true).matches_names("index.html", Some("INDEX~2.HTL")));
+        assert!(!filter.clone().with_short_names(true).matches_names("index.htmx.gz", Some("INDEXH~1.GZ")));
+        assert!(WildcardFilter::new("progra~1").with_short_names(true).matches_names("Program Files", Some("PROGRA~1")));
+
+        // the aliases are generated when they're not given:
+        assert!(filter.clone().with_short_names(true).matches("index.html"));
+        assert!(!filter.with_short_names(true).matches("index.htmx.gz"));
+        assert!(WildcardFilter::new("progra~1").with_short_names(true).matches("Program Files"));
+    }
+}
@@ -0,0 +1,28 @@
+#!/bin/sh
+# stand-in for the powershell side of "Session", speaks the same framing but answers from the fixtures instead
+# of running the commands. unknown commands are echoed back as their stdout.
+
+fixtures=$(dirname "$0")
+count=0
+
+encode() {
+    base64 | tr -d '\n'
+}
+
+echo "Windows PowerShell stand-in"
+
+while IFS= read -r line; do
+    count=$((count + 1))
+    command=$(printf '%s' "$line" | base64 -d)
+
+    case "$command" in
+        Get-Count)
+            echo "#frame 1 $(printf '%s' "$count" | encode) " ;;
+        Get-ChildItem*)
+            echo "#frame 1 $(encode < "$fixtures/folder_listing.json") " ;;
+        Get-Item*missing*)
+            echo "#frame 0  $(printf "Get-Item : Cannot find path 'C:\\\\missing' because it does not exist.\r\n    + FullyQualifiedErrorId : PathNotFound,Microsoft.PowerShell.Commands.GetItemCommand" | encode)" ;;
+        *)
+            echo "#frame 1 $line " ;;
+    esac
+done